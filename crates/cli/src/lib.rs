@@ -1,12 +1,22 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use clap::{Parser as ClapParser, Subcommand, ValueEnum};
 use regex::Regex;
+use serde::Serialize;
+use serde::ser::{SerializeSeq, Serializer};
 
-use linkerland_parser::parse;
+use linkerland_metrics::{
+    CrateMetrics, GapMetrics, Metrics, Rules, build_metrics_where, demangle, stream_metrics_where,
+};
+use linkerland_parser::{
+    MapContents, MapDialect, MapFile, Symbol, SymbolKind, SymbolStream, is_compressed, load,
+    load_reader, stream, stream_as, stream_reader, stream_reader_as,
+};
 use linkerland_tui::run as tui_run;
 
 #[derive(ClapParser, Debug)]
@@ -14,17 +24,27 @@ use linkerland_tui::run as tui_run;
 pub struct Cli {
     #[arg(value_parser = validate_map_path, required = false)]
     pub mapfile: Option<PathBuf>,
+    /// Parse the map as this linker dialect instead of detecting it
+    #[arg(long, value_enum)]
+    pub dialect: Option<Dialect>,
+    /// TOML file of section classification rules (see the Readme)
+    #[arg(long)]
+    pub rules: Option<PathBuf>,
 
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
 
+/// Map path argument meaning "read the map from stdin".
+const STDIN: &str = "-";
+
+/// Accept `.map` files, compressed maps whatever their name, and stdin.
 fn validate_map_path(s: &str) -> std::result::Result<PathBuf, String> {
     let p = PathBuf::from(s);
-    if p.extension().map(|e| e == "map").unwrap_or(false) {
+    if s == STDIN || p.extension().map(|e| e == "map").unwrap_or(false) || is_compressed(&p) {
         Ok(p)
     } else {
-        Err("expected path ending with .map".into())
+        Err("expected path ending with .map, a gzip/zstd/xz-compressed map, or - for stdin".into())
     }
 }
 
@@ -44,6 +64,12 @@ pub struct VizArgs {
     pub sort: SortKey,
     #[arg(long, value_enum, default_value_t = SortOrder::Desc)]
     pub order: SortOrder,
+    /// Parse the map as this linker dialect instead of detecting it
+    #[arg(long, value_enum)]
+    pub dialect: Option<Dialect>,
+    /// TOML file of section classification rules (see the Readme)
+    #[arg(long)]
+    pub rules: Option<PathBuf>,
 }
 
 #[derive(clap::Args, Debug)]
@@ -54,12 +80,42 @@ pub struct ExportArgs {
     pub format: ExportFormat,
     #[arg(long)]
     pub filter: Option<String>,
+    /// Only export symbols of these kinds (e.g. `--kind fde,cie`)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub kind: Vec<Kind>,
     #[arg(long)]
     pub out: Option<PathBuf>,
     #[arg(long, value_enum, default_value_t = SortKey::Size)]
     pub sort: SortKey,
     #[arg(long, value_enum, default_value_t = SortOrder::Desc)]
     pub order: SortOrder,
+    /// Parse the map as this linker dialect instead of detecting it
+    #[arg(long, value_enum)]
+    pub dialect: Option<Dialect>,
+    /// Write symbols in map order as they are read, or total them up for `--crates`, `--sections` and `--contributions`, without loading the whole map (ld64, lld and mold maps)
+    #[arg(long, conflicts_with_all = ["sort", "order"])]
+    pub stream: bool,
+    /// Write per-crate TEXT/RODATA/DATA/BSS totals instead of symbols, like `cargo bloat --crates`
+    #[arg(long, group = "summary", conflicts_with_all = ["sort", "order"])]
+    pub crates: bool,
+    /// Write per-section sizes and symbol totals instead of symbols
+    #[arg(long, group = "summary", conflicts_with_all = ["sort", "order"])]
+    pub sections: bool,
+    /// Write the bytes each object contributes to each output section instead of symbols
+    #[arg(long, group = "summary", conflicts_with_all = ["sort", "order"])]
+    pub contributions: bool,
+    /// Add `[padding]` and `[unknown]` rows for the bytes between symbols to `--contributions`
+    #[arg(long, requires = "contributions")]
+    pub gaps: bool,
+    /// TOML file of section classification rules for `--crates`, `--sections` and `--contributions` (see the Readme)
+    #[arg(long, requires = "summary")]
+    pub rules: Option<PathBuf>,
+    /// Write Rust (legacy and v0), Itanium C++ and MSVC symbol names demangled
+    #[arg(long)]
+    pub demangle: bool,
+    /// Leave the `::h<hash>` suffix out of demangled names
+    #[arg(long, requires = "demangle")]
+    pub strip_hash: bool,
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
@@ -78,6 +134,65 @@ pub enum ExportFormat {
     Json,
     Csv,
 }
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum Dialect {
+    Ld64,
+    Gnu,
+    Lld,
+    Mold,
+    Gold,
+    Msvc,
+    Armlink,
+    Iar,
+    Ti,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum Kind {
+    Function,
+    Data,
+    LiteralString,
+    LiteralCstring,
+    Fde,
+    Cie,
+    ExceptionTable,
+    Anonymous,
+    Stub,
+    LinkerSynthesized,
+}
+
+impl From<Kind> for SymbolKind {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::Function => SymbolKind::Function,
+            Kind::Data => SymbolKind::Data,
+            Kind::LiteralString => SymbolKind::LiteralString,
+            Kind::LiteralCstring => SymbolKind::LiteralCString,
+            Kind::Fde => SymbolKind::Fde,
+            Kind::Cie => SymbolKind::Cie,
+            Kind::ExceptionTable => SymbolKind::ExceptionTable,
+            Kind::Anonymous => SymbolKind::Anonymous,
+            Kind::Stub => SymbolKind::Stub,
+            Kind::LinkerSynthesized => SymbolKind::LinkerSynthesized,
+        }
+    }
+}
+
+impl From<Dialect> for MapDialect {
+    fn from(dialect: Dialect) -> Self {
+        match dialect {
+            Dialect::Ld64 => MapDialect::Ld64,
+            Dialect::Gnu => MapDialect::GnuLd,
+            Dialect::Lld => MapDialect::Lld,
+            Dialect::Mold => MapDialect::Mold,
+            Dialect::Gold => MapDialect::Gold,
+            Dialect::Msvc => MapDialect::Msvc,
+            Dialect::Armlink => MapDialect::Armlink,
+            Dialect::Iar => MapDialect::Iar,
+            Dialect::Ti => MapDialect::Ti,
+        }
+    }
+}
 
 pub fn run() -> Result<()> {
     run_with(Cli::parse())
@@ -90,6 +205,8 @@ pub fn run_with(cli: Cli) -> Result<()> {
             filter: None,
             sort: SortKey::Size,
             order: SortOrder::Desc,
+            dialect: cli.dialect,
+            rules: cli.rules,
         })?;
         return Ok(());
     }
@@ -101,26 +218,90 @@ pub fn run_with(cli: Cli) -> Result<()> {
     Ok(())
 }
 
+fn read(mapfile: &Path) -> Result<MapContents> {
+    Ok(if mapfile == Path::new(STDIN) {
+        load_reader(io::stdin().lock())?
+    } else {
+        load(mapfile)?
+    })
+}
+
+/// Parse `contents`, borrowing names from it rather than copying them.
+fn parse_map<'a>(
+    mapfile: &Path,
+    contents: &'a MapContents,
+    dialect: Option<Dialect>,
+) -> Result<MapFile<'a>> {
+    let map = match dialect {
+        Some(dialect) => contents.parse_as(dialect.into())?,
+        None => contents.parse()?,
+    };
+    warn_invalid_utf8(mapfile, map.invalid_utf8);
+    Ok(map)
+}
+
+fn open_stream(mapfile: &Path, dialect: Option<Dialect>) -> Result<SymbolStream<'static>> {
+    Ok(match (mapfile == Path::new(STDIN), dialect) {
+        (true, Some(dialect)) => stream_reader_as(io::stdin().lock(), dialect.into())?,
+        (true, None) => stream_reader(io::stdin().lock())?,
+        (false, Some(dialect)) => stream_as(mapfile, dialect.into())?,
+        (false, None) => stream(mapfile)?,
+    })
+}
+
+/// Section classification rules from `path`, or the built-in ones for the map.
+fn load_rules(path: Option<&Path>, map: &MapFile) -> Result<Rules> {
+    Ok(match path {
+        Some(path) => Rules::load(path, map.binary_format)?,
+        None => Rules::for_format(map.binary_format),
+    })
+}
+
+fn warn_invalid_utf8(mapfile: &Path, invalid_utf8: usize) {
+    if invalid_utf8 > 0 {
+        eprintln!(
+            "warning: {}: replaced {} invalid UTF-8 sequence(s)",
+            mapfile.display(),
+            invalid_utf8
+        );
+    }
+}
+
 fn viz(args: VizArgs) -> Result<()> {
-    parse(&args.mapfile).map_err(|e| anyhow::anyhow!("parse error: {:?}", e))?;
-    if let Err(e) = tui_run(args.mapfile.to_string_lossy().as_ref()) {
+    let contents = read(&args.mapfile)?;
+    let map = parse_map(&args.mapfile, &contents, args.dialect)?;
+    let rules = load_rules(args.rules.as_deref(), &map)?;
+    if let Err(e) = tui_run(map, &rules) {
         eprintln!("TUI error: {e}");
     }
     Ok(())
 }
 
 fn export(args: ExportArgs) -> Result<()> {
-    let map = parse(&args.mapfile).map_err(|e| anyhow::anyhow!("parse error: {:?}", e))?;
+    if args.crates || args.sections || args.contributions {
+        return export_summary(&args);
+    }
+    if args.stream {
+        return export_stream(&args);
+    }
+    let contents = read(&args.mapfile)?;
+    let mut map = parse_map(&args.mapfile, &contents, args.dialect)?;
+    if args.demangle {
+        for symbol in &mut map.symbols {
+            demangle_name(symbol, args.strip_hash);
+        }
+    }
     let mut symbols: Vec<_> = map.symbols.iter().collect();
     if let Some(f) = &args.filter {
         let re = Regex::new(f)?;
         symbols.retain(|s| re.is_match(&s.name));
     }
+    symbols.retain(|s| kind_matches(s, &args.kind));
     symbols.sort_by(|a, b| {
         use SortKey::*;
         use SortOrder::*;
         let ord = match args.sort {
-            Size => compare_hex_size(&a.size, &b.size),
+            Size => a.size.cmp(&b.size),
             Name => a.name.cmp(&b.name),
             Path => a.file_index.cmp(&b.file_index),
         };
@@ -136,29 +317,216 @@ fn export(args: ExportArgs) -> Result<()> {
     Ok(())
 }
 
-fn compare_hex_size(a: &str, b: &str) -> std::cmp::Ordering {
-    let pa = u64::from_str_radix(a.trim_start_matches("0x"), 16).unwrap_or(0);
-    let pb = u64::from_str_radix(b.trim_start_matches("0x"), 16).unwrap_or(0);
-    pa.cmp(&pb)
+/// Export symbols one at a time, so memory use does not grow with the map.
+fn export_stream(args: &ExportArgs) -> Result<()> {
+    let mut stream = open_stream(&args.mapfile, args.dialect)?;
+    let re = args.filter.as_deref().map(Regex::new).transpose()?;
+    let symbols = stream.by_ref().map(|symbol| {
+        symbol.map(|mut symbol| {
+            if args.demangle {
+                demangle_name(&mut symbol, args.strip_hash);
+            }
+            symbol
+        })
+    });
+    let symbols = symbols.filter(|symbol| match symbol {
+        Ok(symbol) => {
+            re.as_ref().is_none_or(|re| re.is_match(&symbol.name))
+                && kind_matches(symbol, &args.kind)
+        }
+        Err(_) => true,
+    });
+    match args.format {
+        ExportFormat::Json => {
+            let mut ser = serde_json::Serializer::pretty(output(args)?);
+            let mut seq = ser.serialize_seq(None)?;
+            for symbol in symbols {
+                seq.serialize_element(&symbol?)?;
+            }
+            seq.end()?;
+            writeln!(ser.into_inner())?;
+        }
+        ExportFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(output(args)?);
+            for symbol in symbols {
+                wtr.serialize(symbol?)?;
+            }
+            wtr.flush()?;
+        }
+    }
+    warn_invalid_utf8(&args.mapfile, stream.header().invalid_utf8);
+    Ok(())
 }
 
-fn export_json(symbols: &[&linkerland_parser::Symbol], args: &ExportArgs) -> Result<()> {
-    let mut out: Box<dyn Write> = if let Some(path) = &args.out {
-        Box::new(File::create(path)?)
+/// Export the `--crates`, `--sections` or `--contributions` summary.
+///
+/// Only the symbols matching `--filter` and `--kind` are counted. With
+/// `--stream` they are totalled up as they are read instead of after loading
+/// the whole map.
+fn export_summary(args: &ExportArgs) -> Result<()> {
+    let re = args.filter.as_deref().map(Regex::new).transpose()?;
+    let keep = |s: &Symbol| {
+        re.as_ref().is_none_or(|re| re.is_match(&s.name)) && kind_matches(s, &args.kind)
+    };
+    let (metrics, rules) = if args.stream {
+        let mut stream = open_stream(&args.mapfile, args.dialect)?;
+        let rules = load_rules(args.rules.as_deref(), stream.header())?;
+        let metrics = stream_metrics_where(&mut stream, &rules, keep)?;
+        warn_invalid_utf8(&args.mapfile, stream.header().invalid_utf8);
+        (metrics, rules)
     } else {
-        Box::new(io::stdout())
+        let contents = read(&args.mapfile)?;
+        let map = parse_map(&args.mapfile, &contents, args.dialect)?;
+        let rules = load_rules(args.rules.as_deref(), &map)?;
+        (build_metrics_where(&map, &rules, keep), rules)
     };
-    serde_json::to_writer_pretty(&mut out, &symbols)?;
-    writeln!(out)?;
+    if args.crates {
+        export_crates(&metrics.crates, &rules, args)
+    } else if args.sections {
+        export_sections(&metrics, args)
+    } else {
+        export_contributions(&metrics, args)
+    }
+}
+
+/// Export symbol sizes rolled up by Rust crate, largest first.
+///
+/// In CSV, buckets defined by `--rules` get a column each between `other` and `total`.
+fn export_crates(crates: &[CrateMetrics], rules: &Rules, args: &ExportArgs) -> Result<()> {
+    match args.format {
+        ExportFormat::Json => write_json(crates, args)?,
+        ExportFormat::Csv => {
+            let custom = rules.custom_buckets();
+            let mut wtr = csv::Writer::from_writer(output(args)?);
+            let mut header = vec!["name", "text", "rodata", "data", "bss", "other"];
+            header.extend(custom.iter().map(|name| &**name));
+            header.push("total");
+            wtr.write_record(&header)?;
+            for c in crates {
+                let mut record = vec![c.name.clone()];
+                record.extend(
+                    [c.text, c.rodata, c.data, c.bss, c.other].map(|size| size.to_string()),
+                );
+                record.extend(
+                    custom
+                        .iter()
+                        .map(|name| c.custom.get(&**name).copied().unwrap_or(0).to_string()),
+                );
+                record.push(c.total.to_string());
+                wtr.write_record(&record)?;
+            }
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Export every output section in address order with the symbols placed in it.
+fn export_sections(metrics: &Metrics, args: &ExportArgs) -> Result<()> {
+    match args.format {
+        ExportFormat::Json => write_json(&metrics.sections, args)?,
+        ExportFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(output(args)?);
+            for section in &metrics.sections {
+                wtr.serialize(section)?;
+            }
+            wtr.flush()?;
+        }
+    }
     Ok(())
 }
 
-fn export_csv(symbols: &[&linkerland_parser::Symbol], args: &ExportArgs) -> Result<()> {
-    let mut wtr: csv::Writer<Box<dyn Write>> = if let Some(path) = &args.out {
-        csv::Writer::from_writer(Box::new(File::create(path)?))
+/// One cell of the object × section matrix, with names instead of indices.
+#[derive(Serialize)]
+struct ContributionRecord<'a> {
+    object: &'a str,
+    segment: &'a str,
+    section: &'a str,
+    size: u64,
+    symbols: usize,
+}
+
+/// Export the non-empty cells of the object × section matrix, by object then section address.
+///
+/// `--gaps` adds the padding and unattributed bytes of each section after the objects.
+fn export_contributions(metrics: &Metrics, args: &ExportArgs) -> Result<()> {
+    let gaps = if args.gaps {
+        &metrics.gaps
     } else {
-        csv::Writer::from_writer(Box::new(io::stdout()))
+        &GapMetrics::default()
     };
+    let paths: HashMap<u32, &str> = metrics
+        .objects
+        .iter()
+        .chain(&gaps.objects)
+        .map(|object| (object.id, object.path.as_str()))
+        .collect();
+    let records: Vec<ContributionRecord> = metrics
+        .contributions
+        .iter()
+        .chain(&gaps.contributions)
+        .map(|cell| {
+            let section = &metrics.sections[cell.section];
+            ContributionRecord {
+                object: paths.get(&cell.object_id).copied().unwrap_or_default(),
+                segment: &section.segment,
+                section: &section.section,
+                size: cell.size,
+                symbols: cell.symbols,
+            }
+        })
+        .collect();
+    match args.format {
+        ExportFormat::Json => write_json(&records, args)?,
+        ExportFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(output(args)?);
+            for record in &records {
+                wtr.serialize(record)?;
+            }
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn write_json<T: Serialize + ?Sized>(value: &T, args: &ExportArgs) -> Result<()> {
+    let mut out = output(args)?;
+    serde_json::to_writer_pretty(&mut out, value)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Replace the name of a mangled Rust or C++ symbol with its demangled form.
+fn demangle_name(symbol: &mut Symbol<'_>, strip_hash: bool) {
+    if let Some(demangled) = demangle(&symbol.name, strip_hash) {
+        symbol.name = Cow::Owned(demangled);
+    }
+}
+
+/// Returns true if no kinds were requested or `symbol` is one of them.
+fn kind_matches(symbol: &Symbol<'_>, kinds: &[Kind]) -> bool {
+    kinds.is_empty()
+        || kinds
+            .iter()
+            .any(|&kind| SymbolKind::from(kind) == symbol.kind)
+}
+
+fn output(args: &ExportArgs) -> Result<Box<dyn Write>> {
+    Ok(match &args.out {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    })
+}
+
+fn export_json(symbols: &[&linkerland_parser::Symbol<'_>], args: &ExportArgs) -> Result<()> {
+    let mut out = output(args)?;
+    serde_json::to_writer_pretty(&mut out, &symbols)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn export_csv(symbols: &[&linkerland_parser::Symbol<'_>], args: &ExportArgs) -> Result<()> {
+    let mut wtr = csv::Writer::from_writer(output(args)?);
     for s in symbols {
         wtr.serialize(s)?;
     }
@@ -174,4 +542,26 @@ mod tests {
     fn invalid_extension_rejected() {
         assert!(validate_map_path("foo.txt").is_err());
     }
+
+    #[test]
+    fn compressed_maps_recognized_by_contents() {
+        assert!(validate_map_path("../parser/tests/fixtures/lld.map.gz").is_ok());
+        assert!(validate_map_path("../parser/tests/fixtures/gold.map.xz").is_ok());
+
+        let compressed = std::fs::read("../parser/tests/fixtures/mold.map.zst").unwrap();
+        for name in ["linkerland-cli-app.map.zstd", "linkerland-cli-archived-map"] {
+            let path = std::env::temp_dir().join(name);
+            std::fs::write(&path, &compressed).unwrap();
+            assert!(validate_map_path(path.to_str().unwrap()).is_ok(), "{name}");
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        assert!(validate_map_path("Cargo.toml").is_err());
+        assert!(validate_map_path("app.tar.gz").is_err());
+    }
+
+    #[test]
+    fn stdin_accepted() {
+        assert_eq!(validate_map_path("-").unwrap(), PathBuf::from("-"));
+    }
 }
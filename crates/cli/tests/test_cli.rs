@@ -19,3 +19,306 @@ fn export_parses() {
         .assert()
         .failure();
 }
+
+#[test]
+fn export_with_dialect_override() {
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    cmd.args([
+        "export",
+        "../parser/tests/fixtures/gnu_ld.map",
+        "--dialect",
+        "gnu",
+    ])
+    .assert()
+    .success();
+
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    cmd.args(["export", "file.map", "--dialect", "borland"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn missing_map_reports_error() {
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    let output = cmd.args(["export", "missing.map"]).output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("missing.map: no such file"), "{stderr}");
+    assert!(!stderr.contains("panicked"), "{stderr}");
+}
+
+#[test]
+fn export_reads_stdin() {
+    let map = std::fs::read("../parser/tests/fixtures/lld.map").unwrap();
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    let output = cmd
+        .args(["export", "-", "--format", "csv"])
+        .write_stdin(map)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.starts_with("0x") && line.ends_with(",main")),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn export_reads_compressed_map() {
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    cmd.args(["export", "../parser/tests/fixtures/lld.map.gz"])
+        .assert()
+        .success();
+
+    let compressed = std::fs::read("../parser/tests/fixtures/mold.map.zst").unwrap();
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    cmd.args(["export", "-"])
+        .write_stdin(compressed)
+        .assert()
+        .success();
+}
+
+#[test]
+fn export_stream_matches_sorted_export() {
+    let map = "../parser/tests/fixtures/linker.map";
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    let streamed = cmd
+        .args(["export", map, "--stream", "--filter", "main"])
+        .output()
+        .unwrap();
+    assert!(streamed.status.success());
+    let streamed: serde_json::Value = serde_json::from_slice(&streamed.stdout).unwrap();
+
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    let sorted = cmd
+        .args(["export", map, "--filter", "main"])
+        .output()
+        .unwrap();
+    let sorted: serde_json::Value = serde_json::from_slice(&sorted.stdout).unwrap();
+    assert!(!streamed.as_array().unwrap().is_empty());
+    assert_eq!(
+        streamed.as_array().unwrap().len(),
+        sorted.as_array().unwrap().len()
+    );
+
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    cmd.args(["export", map, "--stream", "--sort", "name"])
+        .assert()
+        .failure();
+
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    let output = cmd
+        .args(["export", "../parser/tests/fixtures/gnu_ld.map", "--stream"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("cannot be streamed"), "{stderr}");
+}
+
+#[test]
+fn streamed_summaries_match_loaded_ones() {
+    let summaries: [&[&str]; 4] = [
+        &["--crates"],
+        &["--sections"],
+        &["--contributions", "--gaps"],
+        &["--crates", "--format", "csv", "--kind", "function"],
+    ];
+    for map in [
+        "../parser/tests/fixtures/linker.map",
+        "../parser/tests/fixtures/lld.map",
+        "../parser/tests/fixtures/mold.map",
+    ] {
+        for summary in summaries {
+            let mut cmd = Command::cargo_bin("linkerland").unwrap();
+            let loaded = cmd.args(["export", map]).args(summary).output().unwrap();
+            assert!(loaded.status.success());
+
+            let mut cmd = Command::cargo_bin("linkerland").unwrap();
+            let streamed = cmd
+                .args(["export", map, "--stream"])
+                .args(summary)
+                .output()
+                .unwrap();
+            assert!(streamed.status.success());
+            assert_eq!(streamed.stdout, loaded.stdout, "{map} {summary:?}");
+        }
+    }
+}
+
+#[test]
+fn export_filters_by_kind() {
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    let output = cmd
+        .args([
+            "export",
+            "../parser/tests/fixtures/linker.map",
+            "--format",
+            "csv",
+            "--kind",
+            "fde,exception-table",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some("address,size,file_index,kind,name"));
+    let rows: Vec<&str> = lines.collect();
+    assert_eq!(rows.len(), 535 + 169);
+    assert!(
+        rows.iter().all(|row| row.contains(",Fde,FDE for: ")
+            || row.contains(",ExceptionTable,GCC_except_table"))
+    );
+}
+
+#[test]
+fn export_crates_rolls_up_symbols() {
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    let output = cmd
+        .args([
+            "export",
+            "../parser/tests/fixtures/linker.map",
+            "--crates",
+            "--format",
+            "csv",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some("name,text,rodata,data,bss,other,total"));
+    let crates: Vec<&str> = lines.map(|line| line.split(',').next().unwrap()).collect();
+    assert_eq!(&crates[..3], ["core", "std", "gimli"]);
+    assert!(crates.contains(&"[Unknown]"));
+}
+
+#[test]
+fn export_demangles_rust_symbols() {
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    let output = cmd
+        .args([
+            "export",
+            "../parser/tests/fixtures/linker.map",
+            "--demangle",
+            "--strip-hash",
+            "--filter",
+            "^std::panicking::panic_count::GLOBAL_PANIC_COUNT$",
+            "--format",
+            "csv",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let rows: Vec<&str> = stdout.lines().skip(1).collect();
+    assert_eq!(rows.len(), 1, "{stdout}");
+    assert!(rows[0].ends_with(",std::panicking::panic_count::GLOBAL_PANIC_COUNT"));
+}
+
+#[test]
+fn export_crates_with_custom_buckets() {
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    let output = cmd
+        .args([
+            "export",
+            "../parser/tests/fixtures/linker.map",
+            "--crates",
+            "--rules",
+            "tests/fixtures/rules.toml",
+            "--format",
+            "csv",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(
+        lines.next(),
+        Some("name,text,rodata,data,bss,other,EH,total")
+    );
+    let eh: u64 = lines
+        .map(|line| line.split(',').nth(6).unwrap().parse::<u64>().unwrap())
+        .sum();
+    assert!(eh > 0);
+}
+
+#[test]
+fn export_sections_totals_symbols() {
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    let output = cmd
+        .args([
+            "export",
+            "../parser/tests/fixtures/gnu_ld.map",
+            "--sections",
+            "--format",
+            "csv",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(
+        lines.next(),
+        Some(
+            "segment,section,bucket,start,end,size,symbol_size,symbols,padding,unknown,overlap,largest_symbol,largest_symbol_size"
+        )
+    );
+    let text = lines.find(|line| line.starts_with(".text,.text,")).unwrap();
+    assert!(text.starts_with(".text,.text,Text,"), "{text}");
+    // 448 bytes: 438 in symbols plus 10 of alignment padding.
+    assert!(text.contains(",448,438,7,10,0,0,"), "{text}");
+}
+
+#[test]
+fn export_contributions_per_object_and_section() {
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    let output = cmd
+        .args([
+            "export",
+            "../parser/tests/fixtures/gnu_ld.map",
+            "--contributions",
+            "--format",
+            "csv",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some("object,segment,section,size,symbols"));
+    let rows: Vec<&str> = lines.filter(|line| line.starts_with("main.o,")).collect();
+    assert_eq!(
+        rows,
+        [
+            "main.o,.text,.text,64,2",
+            "main.o,.rodata,.rodata,60,1",
+            "main.o,.data,.data,16,1",
+            "main.o,.bss,.bss,8,1",
+        ]
+    );
+    assert!(!stdout.contains("[padding]"), "{stdout}");
+
+    let mut cmd = Command::cargo_bin("linkerland").unwrap();
+    let output = cmd
+        .args([
+            "export",
+            "../parser/tests/fixtures/gnu_ld.map",
+            "--contributions",
+            "--gaps",
+            "--format",
+            "csv",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\n[padding],.text,.text,10,"), "{stdout}");
+}
@@ -0,0 +1,176 @@
+//! Rust crate attribution for symbols and object files.
+//!
+//! Mangled symbol paths start with the defining crate (`_ZN5gimli4read…`,
+//! `_RNvCs…_5gimli…`), so a symbol is attributed to that crate first. Symbols
+//! that are not Rust paths (C functions, `anon`, `GCC_except_table…`) fall back
+//! to the crate their codegen unit belongs to (`gimli-1ab316f0.gimli.…-cgu.2.rcgu.o`).
+
+/// Crate name reported for symbols that cannot be attributed, as `cargo bloat` does.
+pub const UNKNOWN_CRATE: &str = "[Unknown]";
+
+/// Crate named by a mangled Rust symbol, if `name` is one.
+pub fn symbol_crate(name: &str) -> Option<&str> {
+    // ld64 names unwind entries after the function they describe.
+    let name = name.strip_prefix("FDE for: ").unwrap_or(name);
+    // Itanium C++ names share the legacy `_ZN` prefix.
+    if rustc_demangle::try_demangle(name).is_err() {
+        return None;
+    }
+    // Mach-O prefixes every C-level name with an extra underscore.
+    let name = name.strip_prefix('_').unwrap_or(name);
+    let name = name.strip_prefix('_').unwrap_or(name);
+    if let Some(path) = name.strip_prefix("ZN") {
+        legacy_crate(path)
+    } else if let Some(path) = name.strip_prefix('R') {
+        v0_crate(path)
+    } else {
+        None
+    }
+}
+
+/// Crate of a legacy-mangled path (`5gimli4read…` after `_ZN`).
+fn legacy_crate(path: &str) -> Option<&str> {
+    let (ident, _) = length_prefixed(path)?;
+    let Some(impl_header) = ident
+        .strip_prefix("_$LT$")
+        .or_else(|| ident.strip_prefix("$LT$"))
+    else {
+        return Some(ident).filter(|ident| is_identifier(ident));
+    };
+
+    // `<Type as Trait>`: the crate of the type if it has a path, else of the trait.
+    let (self_type, trait_path) = match impl_header.split_once("$u20$as$u20$") {
+        Some((self_type, trait_path)) => (self_type, Some(trait_path)),
+        None => (impl_header, None),
+    };
+    let mut self_type = self_type;
+    while let Some(rest) = ["$RF$", "$BP$", "mut$u20$", "dyn$u20$", "$u5b$"]
+        .iter()
+        .find_map(|prefix| self_type.strip_prefix(prefix))
+    {
+        self_type = rest;
+    }
+    path_root(self_type).or_else(|| trait_path.and_then(path_root))
+}
+
+/// First segment of a demangled-then-escaped path (`alloc..string..String` -> `alloc`).
+fn path_root(path: &str) -> Option<&str> {
+    let (root, _) = path.split_once("..")?;
+    Some(root).filter(|root| is_identifier(root))
+}
+
+/// Crate of a v0-mangled path (`NvCs1a2b_5gimli4read` after `_R`).
+fn v0_crate(mut path: &str) -> Option<&str> {
+    // Nested paths (`N` + namespace) wrap the crate root (`C`).
+    while let Some(rest) = path.strip_prefix('N') {
+        let mut chars = rest.chars();
+        chars.next().filter(char::is_ascii_alphabetic)?;
+        path = chars.as_str();
+    }
+    let mut path = path.strip_prefix('C')?;
+    // Optional disambiguator: `s<base-62>_`.
+    if let Some(rest) = path.strip_prefix('s') {
+        path = &rest[rest.find('_')? + 1..];
+    }
+    let (ident, _) = length_prefixed(path)?;
+    Some(ident).filter(|ident| is_identifier(ident))
+}
+
+/// Split a `<decimal length><bytes>` identifier off `input`.
+fn length_prefixed(input: &str) -> Option<(&str, &str)> {
+    let digits = input.bytes().take_while(u8::is_ascii_digit).count();
+    let len: usize = input[..digits].parse().ok()?;
+    let rest = &input[digits..];
+    rest.get(..len).map(|ident| (ident, &rest[len..]))
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+/// Crate a Rust object file belongs to, from its file name.
+///
+/// Accepts codegen units (`gimli-1ab316f099134a82.gimli.86b73ce1-cgu.2.rcgu.o`)
+/// and rlibs (`libgimli-1ab316f099134a82.rlib`).
+pub fn object_crate(name: &str) -> Option<&str> {
+    let file = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let stem = if file.ends_with(".rcgu.o") {
+        file
+    } else {
+        file.strip_suffix(".rlib")?.strip_prefix("lib")?
+    };
+    let end = stem.find(['-', '.']).unwrap_or(stem.len());
+    Some(&stem[..end]).filter(|name| is_identifier(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_symbols() {
+        assert_eq!(
+            symbol_crate("__ZN5gimli4read6abbrev17h0123456789abcdefE"),
+            Some("gimli")
+        );
+        assert_eq!(
+            symbol_crate("_ZN3std2rt10lang_start17h0123456789abcdefE.llvm.42"),
+            Some("std")
+        );
+        assert_eq!(
+            symbol_crate("FDE for: __ZN4core3fmt5write17h0123456789abcdefE"),
+            Some("core")
+        );
+        assert_eq!(
+            symbol_crate("__ZN64_$LT$std..ffi..os_str..Display$u20$as$u20$core..fmt..Display$GT$3fmt17hd222e32362c3667aE"),
+            Some("std")
+        );
+        assert_eq!(
+            symbol_crate(
+                "__ZN42_$LT$$RF$T$u20$as$u20$core..fmt..Debug$GT$3fmt17hfa1e3f5ae07945bcE"
+            ),
+            Some("core")
+        );
+        assert_eq!(
+            symbol_crate("__ZN54_$LT$$LP$$RP$$u20$as$u20$std..process..Termination$GT$6report17h5580027413c95f8dE"),
+            Some("std")
+        );
+    }
+
+    #[test]
+    fn test_v0_symbols() {
+        assert_eq!(
+            symbol_crate("_RNvNtCs1234abcd_5gimli4read5parse"),
+            Some("gimli")
+        );
+        assert_eq!(symbol_crate("_RNvCs1234_4core3foo"), Some("core"));
+        assert_eq!(symbol_crate("_RNvMs_Cs1234_4core3foo"), None);
+    }
+
+    #[test]
+    fn test_non_rust_symbols() {
+        assert_eq!(symbol_crate("_malloc"), None);
+        assert_eq!(symbol_crate("___rust_alloc"), None);
+        assert_eq!(symbol_crate("GCC_except_table12"), None);
+        assert_eq!(symbol_crate("anon"), None);
+        assert_eq!(symbol_crate("__ZN3foo3barEi"), None);
+    }
+
+    #[test]
+    fn test_object_crate() {
+        assert_eq!(
+            object_crate("gimli-1ab316f099134a82.gimli.86b73ce1986d3c1-cgu.2.rcgu.o"),
+            Some("gimli")
+        );
+        assert_eq!(
+            object_crate("/target/debug/deps/learning_linkers-97732971bdfee10d.0hl7d1c5593534mdzur6vmtpi.rcgu.o"),
+            Some("learning_linkers")
+        );
+        assert_eq!(
+            object_crate("/toolchain/lib/libstd-5f11fb1f9c11a5d1.rlib"),
+            Some("std")
+        );
+        assert_eq!(object_crate("main.o"), None);
+        assert_eq!(object_crate("/usr/lib/libc.a"), None);
+    }
+}
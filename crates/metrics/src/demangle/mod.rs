@@ -0,0 +1,220 @@
+//! Demangling of Rust and C++ symbol names.
+//!
+//! The scheme is picked per symbol, so mixed-language binaries read naturally:
+//!
+//! - Rust legacy (`_ZN…17h<hash>E`) and v0 (`_R…`) names, including the
+//!   `.llvm.NNN` suffixes ThinLTO appends to imported internal symbols
+//! - Itanium C++ names (`_Z…`), as used on ELF and Mach-O
+//! - MSVC decorated names (`?name@scope@@…`)
+//!
+//! Mach-O's extra leading underscore (`__ZN…`) is accepted everywhere.
+
+use std::thread;
+
+use msvc_demangler::DemangleFlags;
+
+/// Like the Itanium names, MSVC ones are printed without return types, access
+/// specifiers or calling conventions: `util::Stack<int>::push(int)`.
+const MSVC_FLAGS: DemangleFlags = DemangleFlags::COMPLETE
+    .union(DemangleFlags::SPACE_AFTER_COMMA)
+    .union(DemangleFlags::SPACE_BEFORE_POINTER)
+    .union(DemangleFlags::MS_TYPENAMES)
+    .union(DemangleFlags::HUG_TYPE)
+    .union(DemangleFlags::NO_FUNCTION_RETURNS)
+    .union(DemangleFlags::NO_ACCESS_SPECIFIERS)
+    .union(DemangleFlags::NO_MEMBER_TYPE)
+    .union(DemangleFlags::NO_MS_KEYWORDS)
+    .union(DemangleFlags::NO_CLASS_TYPE);
+
+/// Longest decorated name cl.exe emits; longer ones are hashed to `??@…@`.
+const MAX_MSVC_LEN: usize = 4096;
+
+/// Codes `msvc_demangler` recurses on: pointers, references, templates, arrays
+/// and function types. Counting them bounds how deeply a name can nest.
+const MSVC_NESTING_CODES: &[u8] = b"PQRSAB?$Y6";
+
+/// Nesting codes a name may contain before it is demangled on its own stack.
+const MAX_INLINE_NESTING: usize = 32;
+
+/// Stack reserved per byte of a name demangled on its own thread.
+const STACK_PER_BYTE: usize = 16 * 1024;
+
+/// Demangled form of a Rust or C++ symbol, or `None` if `name` is not mangled.
+///
+/// With `strip_hash`, the trailing `::h<hash>` of legacy Rust symbols is left out.
+/// ld64's `FDE for: …` rows keep their prefix and demangle the function name.
+pub fn demangle(name: &str, strip_hash: bool) -> Option<String> {
+    if let Some(function) = name.strip_prefix("FDE for: ") {
+        return demangle(function, strip_hash).map(|function| format!("FDE for: {function}"));
+    }
+    if let Some(demangled) = rust(name, strip_hash) {
+        return Some(demangled);
+    }
+    if name.starts_with('?') {
+        return msvc(name);
+    }
+    itanium(name)
+}
+
+fn rust(name: &str, strip_hash: bool) -> Option<String> {
+    let demangled = rustc_demangle::try_demangle(name).ok()?;
+    Some(if strip_hash {
+        format!("{demangled:#}")
+    } else {
+        demangled.to_string()
+    })
+}
+
+fn msvc(name: &str) -> Option<String> {
+    if name.len() > MAX_MSVC_LEN {
+        return None;
+    }
+    if name.starts_with("??_C@") {
+        return Some("`string'".to_string());
+    }
+    let nesting = name
+        .bytes()
+        .filter(|byte| MSVC_NESTING_CODES.contains(byte))
+        .count();
+    if nesting <= MAX_INLINE_NESTING {
+        return msvc_demangler::demangle(name, MSVC_FLAGS).ok();
+    }
+    // `msvc_demangler` recurses once per nested type, so give deep names a stack to match.
+    thread::scope(|scope| {
+        thread::Builder::new()
+            .stack_size(name.len() * STACK_PER_BYTE)
+            .spawn_scoped(scope, || msvc_demangler::demangle(name, MSVC_FLAGS).ok())
+            .ok()?
+            .join()
+            .ok()
+            .flatten()
+    })
+}
+
+fn itanium(name: &str) -> Option<String> {
+    let name = name
+        .strip_prefix('_')
+        .filter(|name| name.starts_with("_Z"))
+        .unwrap_or(name);
+    if !name.starts_with("_Z") {
+        return None;
+    }
+    cpp_demangle::Symbol::new(name).ok()?.demangle().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_symbols() {
+        let name = "__ZN3std9panicking11panic_count18GLOBAL_PANIC_COUNT17hb013563ab963f662E";
+        assert_eq!(
+            demangle(name, false).as_deref(),
+            Some("std::panicking::panic_count::GLOBAL_PANIC_COUNT::hb013563ab963f662")
+        );
+        assert_eq!(
+            demangle(name, true).as_deref(),
+            Some("std::panicking::panic_count::GLOBAL_PANIC_COUNT")
+        );
+        assert_eq!(
+            demangle(
+                "_ZN3std2rt10lang_start17h0123456789abcdefE.llvm.4126841",
+                true
+            )
+            .as_deref(),
+            Some("std::rt::lang_start")
+        );
+        assert_eq!(
+            demangle("FDE for: __ZN4core3fmt5write17h0123456789abcdefE", true).as_deref(),
+            Some("FDE for: core::fmt::write")
+        );
+    }
+
+    #[test]
+    fn test_v0_symbols() {
+        assert_eq!(
+            demangle("_RNvNtCs1234abcd_5gimli4read5parse", true).as_deref(),
+            Some("gimli::read::parse")
+        );
+        assert_eq!(
+            demangle("__RNvCs1234_4core3foo", true).as_deref(),
+            Some("core::foo")
+        );
+    }
+
+    #[test]
+    fn test_cpp_symbols() {
+        assert_eq!(
+            demangle("__ZN3foo3barEi", true).as_deref(),
+            Some("foo::bar(int)")
+        );
+        assert_eq!(
+            demangle("_ZNSt6vectorIiSaIiEE9push_backERKi", true).as_deref(),
+            Some("std::vector<int, std::allocator<int> >::push_back(int const&)")
+        );
+        assert_eq!(
+            demangle("?push@?$Stack@H@util@@QEAAXH@Z", true).as_deref(),
+            Some("util::Stack<int>::push(int)")
+        );
+    }
+
+    #[test]
+    fn test_msvc_symbols() {
+        let cases = [
+            ("?main@@YAHXZ", "main(void)"),
+            ("?f@@YAXPEBD0@Z", "f(char const *, char const *)"),
+            ("?printf@@YAHPEBDZZ", "printf(char const *, ...)"),
+            ("?cb@@YAXP6AXH@Z@Z", "cb(void (*)(int))"),
+            ("?size@Widget@@QEBA_KXZ", "Widget::size(void) const"),
+            (
+                "?get@?$Array@H$0BA@@@QEAAAEAHXZ",
+                "Array<int, 16>::get(void)",
+            ),
+            (
+                "??$max@H@std@@YAAEBHAEBH0@Z",
+                "std::max<int>(int const &, int const &)",
+            ),
+            (
+                "??0Widget@ui@@QEAA@AEBV01@@Z",
+                "ui::Widget::Widget(ui::Widget const &)",
+            ),
+            ("??1Widget@ui@@UEAA@XZ", "ui::Widget::~Widget(void)"),
+            (
+                "??4Widget@@QEAAAEAV0@AEBV0@@Z",
+                "Widget::operator=(Widget const &)",
+            ),
+            (
+                "??H@YA?AVString@@AEBV0@0@Z",
+                "operator+(String const &, String const &)",
+            ),
+            ("??Bfoo@@QEBAHXZ", "foo::operator int(void) const"),
+            ("??_7Widget@@6B@", "Widget::`vftable'"),
+            ("?count@@3HA", "count"),
+            ("?instance@Registry@@2PEAV1@EA", "Registry::instance"),
+            ("??_C@_05PDJBBECF@hello@", "`string'"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(demangle(name, true).as_deref(), Some(expected), "{name}");
+        }
+        assert_eq!(demangle("?foo@@Q", true), None);
+    }
+
+    #[test]
+    fn test_deeply_nested_msvc_symbols() {
+        let pointers = format!("?f@@YAX{}H@Z", "PEA".repeat(1000));
+        let demangled = demangle(&pointers, true).unwrap();
+        assert!(demangled.starts_with("f(int *"), "{demangled}");
+        assert_eq!(
+            demangle(&format!("?f@@YAX{}H@Z", "PEA".repeat(2000)), true),
+            None
+        );
+    }
+
+    #[test]
+    fn test_unmangled_symbols() {
+        assert_eq!(demangle("_malloc", true), None);
+        assert_eq!(demangle("GCC_except_table12", true), None);
+        assert_eq!(demangle("literal string: hello", true), None);
+    }
+}
@@ -0,0 +1,136 @@
+//! Bytes of a section that no symbol accounts for, and bytes claimed twice.
+//!
+//! Symbols rarely tile their section exactly. Walking them in address order,
+//! a hole that ends at the next symbol's alignment boundary is padding the
+//! linker inserted; whatever is left of a hole is unattributed, and a symbol
+//! starting before the previous one ends overlaps it.
+
+/// Largest alignment assumed for padding (a cache line); bigger holes are unattributed.
+const MAX_ALIGN: u64 = 64;
+
+/// How the bytes of one section split beyond its symbols.
+///
+/// `size = symbol_size + padding + unknown - overlap` for the section.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Gaps {
+    pub padding: u64,
+    pub unknown: u64,
+    /// Bytes counted by more than one symbol, or past the end of the section
+    pub overlap: u64,
+    /// Every run of padding or unattributed bytes, in address order
+    pub holes: Vec<Hole>,
+}
+
+/// A run of bytes between symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Hole {
+    pub address: u64,
+    pub size: u64,
+    /// Alignment padding rather than unattributed bytes
+    pub padding: bool,
+}
+
+/// Account for the holes and overlaps of the `(address, size)` symbols in `start..end`.
+pub(crate) fn section_gaps(start: u64, end: u64, symbols: &mut [(u64, u64)]) -> Gaps {
+    symbols.sort_unstable();
+    let mut gaps = Gaps::default();
+    let mut cursor = start;
+    for &(address, size) in symbols.iter() {
+        let symbol_end = address.saturating_add(size);
+        if address > cursor {
+            gaps.add_hole(cursor, address);
+        } else {
+            gaps.overlap += symbol_end.min(cursor) - address;
+        }
+        cursor = cursor.max(symbol_end);
+    }
+    if cursor > end {
+        gaps.overlap += cursor - end;
+    } else {
+        gaps.add_hole(cursor, end);
+    }
+    gaps
+}
+
+impl Gaps {
+    /// Split `from..to` into padding up to the alignment of `to`, and unattributed bytes.
+    fn add_hole(&mut self, from: u64, to: u64) {
+        let align = 1u64 << to.trailing_zeros().min(MAX_ALIGN.trailing_zeros());
+        let aligned = from.div_ceil(align).saturating_mul(align);
+        let padding = aligned.min(to) - from;
+        let unknown = to - from - padding;
+        self.padding += padding;
+        self.unknown += unknown;
+        for (address, size, padding) in [(from, padding, true), (to - unknown, unknown, false)] {
+            if size > 0 {
+                self.holes.push(Hole {
+                    address,
+                    size,
+                    padding,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alignment_holes_are_padding() {
+        let mut symbols = [(0x1010, 0x10), (0x1000, 0x0c), (0x1020, 0x1e)];
+        let gaps = section_gaps(0x1000, 0x1040, &mut symbols);
+        assert_eq!((gaps.padding, gaps.unknown, gaps.overlap), (4 + 2, 0, 0));
+        assert_eq!(
+            gaps.holes,
+            [
+                Hole {
+                    address: 0x100c,
+                    size: 4,
+                    padding: true,
+                },
+                Hole {
+                    address: 0x103e,
+                    size: 2,
+                    padding: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn holes_beyond_alignment_are_unattributed() {
+        let mut symbols = [(0x1000, 0x4), (0x1100, 0x8)];
+        let gaps = section_gaps(0x1000, 0x1108, &mut symbols);
+        assert_eq!(gaps.padding, 0x3c);
+        assert_eq!(gaps.unknown, 0x1100 - 0x1040);
+        assert_eq!(gaps.overlap, 0);
+        let holes: Vec<_> = gaps
+            .holes
+            .iter()
+            .map(|hole| (hole.address, hole.size, hole.padding))
+            .collect();
+        assert_eq!(holes, [(0x1004, 0x3c, true), (0x1040, 0xc0, false)]);
+    }
+
+    #[test]
+    fn overlaps_and_overruns() {
+        let mut symbols = [
+            (0x1000, 0x10),
+            (0x1008, 0x10),
+            (0x1010, 0x8),
+            (0x1018, 0x10),
+        ];
+        let gaps = section_gaps(0x1000, 0x1020, &mut symbols);
+        assert_eq!(gaps.overlap, 0x8 + 0x8 + 0x8);
+        assert_eq!(gaps.padding + gaps.unknown, 0);
+    }
+
+    #[test]
+    fn empty_section_is_unattributed() {
+        let gaps = section_gaps(0x1004, 0x1100, &mut []);
+        assert_eq!(gaps.padding, 0x3c);
+        assert_eq!(gaps.unknown, 0xc0);
+    }
+}
@@ -1,21 +1,80 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 
-use linkerland_parser::{MapFile, Section};
+use linkerland_parser::{
+    DeadSymbol, Error, MapFile, ObjectFile, ObjectKind, Section, Symbol, SymbolKind, SymbolStream,
+};
 use serde::Serialize;
 
+mod crates;
+mod demangle;
+mod gaps;
+mod rules;
+
+pub use crates::{object_crate, symbol_crate, UNKNOWN_CRATE};
+pub use demangle::demangle;
+pub use rules::{Preset, Rules, RulesError};
+
+use gaps::section_gaps;
+
+/// Name of the pseudo-object and -symbols holding alignment padding between symbols.
+///
+/// Pseudo-entries live in [`Metrics::gaps`], apart from the objects and symbols
+/// of the map, and are not part of [`GlobalTotals::total`]; their bytes are
+/// [`GlobalTotals::padding`] and [`GlobalTotals::unknown`]. Overlaps get no
+/// pseudo-entry: the bytes are already counted by the symbols that overlap, so
+/// an entry would have to be negative to make sizes add up.
+pub const PADDING: &str = "[padding]";
+/// Name of the pseudo-object and -symbols holding section bytes no symbol accounts for.
+pub const UNKNOWN: &str = "[unknown]";
+/// [`ObjectMetrics::id`] of the [`PADDING`] pseudo-object.
+pub const PADDING_OBJECT_ID: u32 = u32::MAX - 1;
+/// [`ObjectMetrics::id`] of the [`UNKNOWN`] pseudo-object.
+pub const UNKNOWN_OBJECT_ID: u32 = u32::MAX;
+
 /// Memory section classification category.
 ///
-/// Categorizes sections into standard memory types for analysis.
-#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, Hash)]
+/// Categorizes sections into standard memory types for analysis; see [`Rules`].
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum Bucket {
     /// Executable code sections (e.g., `__TEXT/__text`, `.text`)
     Text,
+    /// Read-only data sections (e.g., `__TEXT/__const`, `__TEXT/__cstring`, `.rodata`, `.eh_frame`)
+    Rodata,
     /// Initialized data sections (e.g., `__DATA/__data`, `.data`)
     Data,
     /// Uninitialized data sections (e.g., `__DATA/__bss`, `.bss`)
     Bss,
     /// Other/misc sections (e.g., debug info, metadata)
     Other,
+    /// User-defined bucket from a rules file (e.g., `TLS`, `DEBUG`)
+    Custom(Arc<str>),
+}
+
+impl Bucket {
+    /// Returns the upper-case name of a built-in bucket, or a custom bucket's name.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Bucket::Text => "TEXT",
+            Bucket::Rodata => "RODATA",
+            Bucket::Data => "DATA",
+            Bucket::Bss => "BSS",
+            Bucket::Other => "OTHER",
+            Bucket::Custom(name) => name,
+        }
+    }
+
+    /// The built-in bucket called `name` (in any case), else a custom one.
+    fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "text" => Bucket::Text,
+            "rodata" => Bucket::Rodata,
+            "data" => Bucket::Data,
+            "bss" => Bucket::Bss,
+            "other" => Bucket::Other,
+            _ => Bucket::Custom(name.into()),
+        }
+    }
 }
 
 /// Aggregated metrics for a single object file.
@@ -23,13 +82,51 @@ pub enum Bucket {
 /// Contains size breakdowns by section type for one object file.
 #[derive(Debug, Serialize, Clone)]
 pub struct ObjectMetrics {
-    pub id: i32,
+    pub id: u32,
     pub path: String,
+    /// Static library the object was extracted from, if any
+    pub archive: Option<String>,
+    /// Member name (for archive members) or the full path, for display
+    pub name: String,
+    pub kind: ObjectKind,
+    /// Rust crate the object was compiled from, if its name says so
+    pub crate_name: Option<String>,
     pub text: u64,
+    pub rodata: u64,
     pub data: u64,
     pub bss: u64,
     pub other: u64,
+    /// Bytes per user-defined bucket (not part of `other`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, u64>,
     pub total: u64,
+    /// Bytes of this object's symbols removed by dead-stripping (not part of `total`)
+    pub dead_stripped: u64,
+}
+
+impl ObjectMetrics {
+    /// Returns true if dead-stripping removed everything this object contributed.
+    pub fn only_dead_code(&self) -> bool {
+        self.total == 0 && self.dead_stripped > 0
+    }
+}
+
+/// Aggregated metrics for every member of one static library.
+#[derive(Debug, Serialize, Clone)]
+pub struct ArchiveMetrics {
+    pub path: String,
+    /// Number of members that were linked in
+    pub members: usize,
+    pub text: u64,
+    pub rodata: u64,
+    pub data: u64,
+    pub bss: u64,
+    pub other: u64,
+    /// Bytes per user-defined bucket (not part of `other`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, u64>,
+    pub total: u64,
+    pub dead_stripped: u64,
 }
 
 /// Metrics for a single symbol with section classification.
@@ -39,21 +136,111 @@ pub struct ObjectMetrics {
 pub struct SymbolMetrics {
     pub address: u64,
     pub size: u64,
-    pub file_index: i32,
+    pub file_index: u32,
     pub name: String,
+    /// Demangled Rust or C++ name, once [`Metrics::demangle_symbols`] has run
+    pub demangled_name: Option<String>,
     pub bucket: Bucket,
+    pub kind: SymbolKind,
+    /// Rust crate the symbol belongs to, from its mangled path or else its object
+    pub crate_name: Option<String>,
+}
+
+/// Aggregated metrics for every symbol attributed to one Rust crate.
+///
+/// Like `cargo bloat --crates`; unattributed symbols are grouped under [`UNKNOWN_CRATE`].
+#[derive(Debug, Serialize, Clone)]
+pub struct CrateMetrics {
+    pub name: String,
+    pub text: u64,
+    pub rodata: u64,
+    pub data: u64,
+    pub bss: u64,
+    pub other: u64,
+    /// Bytes per user-defined bucket (not part of `other`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, u64>,
+    pub total: u64,
+}
+
+/// Metrics for one output section and the symbols placed in it.
+#[derive(Debug, Serialize, Clone)]
+pub struct SectionMetrics {
+    pub segment: String,
+    pub section: String,
+    pub bucket: Bucket,
+    pub start: u64,
+    pub end: u64,
+    /// Size the map declares for the section
+    pub size: u64,
+    /// Sum of the sizes of the symbols placed in the section
+    pub symbol_size: u64,
+    pub symbols: usize,
+    /// Alignment padding between and after the symbols
+    pub padding: u64,
+    /// Bytes neither symbols nor padding account for
+    pub unknown: u64,
+    /// Bytes counted by more than one symbol, or by a symbol running past the end
+    pub overlap: u64,
+    /// Name of the largest symbol in the section
+    pub largest_symbol: Option<String>,
+    pub largest_symbol_size: u64,
+}
+
+/// Bytes one object contributes to one output section.
+///
+/// Only non-empty cells of the object × section matrix are kept.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct ContributionMetrics {
+    /// [`ObjectMetrics::id`] of the contributing object
+    pub object_id: u32,
+    /// Position of the section in [`Metrics::sections`]
+    pub section: usize,
+    pub size: u64,
+    pub symbols: usize,
 }
 
 /// Global totals across all objects and symbols.
 ///
 /// Aggregates total sizes for each section type across the entire binary.
+/// When every symbol lies in a section, `total + padding + unknown - overlap`
+/// is the sum of the section sizes.
 #[derive(Debug, Serialize, Clone, Default)]
 pub struct GlobalTotals {
     pub text: u64,
+    pub rodata: u64,
     pub data: u64,
     pub bss: u64,
     pub other: u64,
+    /// Bytes per user-defined bucket (not part of `other`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, u64>,
     pub total: u64,
+    /// Bytes removed by dead-stripping (not part of `total`)
+    pub dead_stripped: u64,
+    /// Alignment padding in all sections (not part of `total`)
+    pub padding: u64,
+    /// Section bytes no symbol accounts for (not part of `total`)
+    pub unknown: u64,
+    /// Bytes counted twice by overlapping symbols (part of `total`)
+    pub overlap: u64,
+    /// Bytes per symbol kind (e.g. how much of `total` is unwind info)
+    pub by_kind: BTreeMap<SymbolKind, u64>,
+}
+
+/// The [`PADDING`] and [`UNKNOWN`] pseudo-entries of a map.
+///
+/// Kept out of [`Metrics::objects`], [`Metrics::symbols`] and
+/// [`Metrics::contributions`] so those only list what the linker placed;
+/// consumers that show gaps add these rows themselves.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct GapMetrics {
+    /// Pseudo-objects with any bytes, padding first
+    pub objects: Vec<ObjectMetrics>,
+    /// One pseudo-symbol per hole, by section then address
+    pub symbols: Vec<SymbolMetrics>,
+    /// Bytes per pseudo-object and section, by object id then section address
+    pub contributions: Vec<ContributionMetrics>,
 }
 
 /// Complete metrics analysis result.
@@ -62,42 +249,81 @@ pub struct GlobalTotals {
 #[derive(Debug, Serialize, Clone)]
 pub struct Metrics {
     pub objects: Vec<ObjectMetrics>,
+    /// Objects rolled up by static library, largest first
+    pub archives: Vec<ArchiveMetrics>,
+    /// Symbols rolled up by Rust crate, largest first
+    pub crates: Vec<CrateMetrics>,
+    /// Output sections in address order
+    pub sections: Vec<SectionMetrics>,
+    /// Bytes per object and section, by object id then section address
+    pub contributions: Vec<ContributionMetrics>,
     pub symbols: Vec<SymbolMetrics>,
+    /// Padding and unattributed bytes, as pseudo-objects and -symbols
+    pub gaps: GapMetrics,
     pub totals: GlobalTotals,
 }
 
+impl Metrics {
+    /// Fill in [`SymbolMetrics::demangled_name`] for every mangled Rust or C++ symbol.
+    ///
+    /// Not done while building, as most consumers never show symbol names.
+    pub fn demangle_symbols(&mut self, strip_hash: bool) {
+        for symbol in &mut self.symbols {
+            symbol.demangled_name = demangle(&symbol.name, strip_hash);
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct SectionRange {
     start: u64,
     end: u64,
-    segment: String,
-    section: String,
+    bucket: Bucket,
 }
 
-fn parse_hex_u64(value: &str) -> Option<u64> {
-    let trimmed = value.strip_prefix("0x").unwrap_or(value);
-    u64::from_str_radix(trimmed, 16).ok()
+/// Empty metrics for `section`, classified with `rules`.
+fn section_metrics(section: &Section, rules: &Rules) -> SectionMetrics {
+    SectionMetrics {
+        segment: section.segment.to_string(),
+        section: section.section.to_string(),
+        bucket: rules.classify(&section.segment, &section.section),
+        start: section.address,
+        end: section.address.saturating_add(section.size),
+        size: section.size,
+        symbol_size: 0,
+        symbols: 0,
+        padding: 0,
+        unknown: 0,
+        overlap: 0,
+        largest_symbol: None,
+        largest_symbol_size: 0,
+    }
 }
 
-fn build_section_ranges(sections: &[Section]) -> Vec<SectionRange> {
-    let mut ranges: Vec<SectionRange> = sections
-        .iter()
-        .filter_map(|section| {
-            let start = parse_hex_u64(&section.address)?;
-            let size = parse_hex_u64(&section.size)?;
-            Some(SectionRange {
-                start,
-                end: start.saturating_add(size),
-                segment: section.segment.clone(),
-                section: section.section.clone(),
-            })
-        })
-        .collect();
-    ranges.sort_by_key(|range| range.start);
-    ranges
+/// Empty metrics for `object_file`.
+fn object_metrics(object_file: &ObjectFile) -> ObjectMetrics {
+    ObjectMetrics {
+        id: object_file.index,
+        path: object_file.path.to_string(),
+        archive: object_file.archive.as_deref().map(str::to_string),
+        name: object_file.display_name().to_string(),
+        kind: object_file.kind,
+        crate_name: object_crate(object_file.display_name())
+            .or_else(|| object_file.archive.as_deref().and_then(object_crate))
+            .map(str::to_string),
+        text: 0,
+        rodata: 0,
+        data: 0,
+        bss: 0,
+        other: 0,
+        custom: BTreeMap::new(),
+        total: 0,
+        dead_stripped: 0,
+    }
 }
 
-fn find_section(ranges: &[SectionRange], address: u64) -> Option<&SectionRange> {
+/// Position of the range containing `address`.
+fn find_section(ranges: &[SectionRange], address: u64) -> Option<usize> {
     let mut low = 0usize;
     let mut high = ranges.len();
     while low < high {
@@ -111,142 +337,483 @@ fn find_section(ranges: &[SectionRange], address: u64) -> Option<&SectionRange>
     if low == 0 {
         return None;
     }
-    let candidate = &ranges[low - 1];
-    (address < candidate.end).then_some(candidate)
+    (address < ranges[low - 1].end).then_some(low - 1)
 }
 
-fn classify(segment: &str, section: &str) -> Bucket {
-    let segment_upper = segment.to_ascii_uppercase();
-    if segment_upper == "__TEXT" {
-        return Bucket::Text;
-    }
-
-    let section_lower = section.to_ascii_lowercase();
-    const DATA_SECTIONS: &[&str] = &[
-        "__data",
-        "__const",
-        "__got",
-        "__mod_init_func",
-        "__cstring",
-        "__const_coal",
-    ];
-    const BSS_SECTIONS: &[&str] = &["__bss", "__bss_coal", "__common"];
-    if DATA_SECTIONS
-        .iter()
-        .any(|candidate| section_lower == *candidate)
-    {
-        return Bucket::Data;
-    }
-    if BSS_SECTIONS
-        .iter()
-        .any(|candidate| section_lower == *candidate)
-    {
-        return Bucket::Bss;
-    }
-    Bucket::Other
+/// Adds symbol sizes to the per-bucket fields of a metrics row.
+trait Tally {
+    fn tally(&mut self, bucket: &Bucket, size: u64);
 }
 
-/// Build aggregated metrics (per-object, per-symbol classification, global totals).
-pub fn build_metrics(map: &MapFile) -> Metrics {
-    let section_ranges = build_section_ranges(&map.sections);
+macro_rules! impl_tally {
+    ($($row:ty),*) => {$(
+        impl Tally for $row {
+            fn tally(&mut self, bucket: &Bucket, size: u64) {
+                match bucket {
+                    Bucket::Text => self.text += size,
+                    Bucket::Rodata => self.rodata += size,
+                    Bucket::Data => self.data += size,
+                    Bucket::Bss => self.bss += size,
+                    Bucket::Other => self.other += size,
+                    Bucket::Custom(name) => match self.custom.get_mut(&**name) {
+                        Some(bytes) => *bytes += size,
+                        None => {
+                            self.custom.insert(name.to_string(), size);
+                        }
+                    },
+                }
+                self.total += size;
+            }
+        }
+    )*};
+}
 
-    let mut objects: Vec<ObjectMetrics> = map
-        .object_files
-        .iter()
-        .map(|object_file| ObjectMetrics {
-            id: object_file.index,
-            path: object_file.path.clone(),
-            text: 0,
-            data: 0,
-            bss: 0,
-            other: 0,
-            total: 0,
-        })
-        .collect();
+impl_tally!(ObjectMetrics, CrateMetrics, GlobalTotals);
 
-    let object_index_lookup: HashMap<i32, usize> = objects
-        .iter()
-        .enumerate()
-        .map(|(position, metrics)| (metrics.id, position))
-        .collect();
+/// Incrementally aggregates metrics one symbol at a time.
+///
+/// Symbols are not kept unless the caller keeps them, so those read from a
+/// [`linkerland_parser::SymbolStream`] can be aggregated without loading the
+/// whole map. Memory still grows with the map: besides the per-object, crate
+/// and section totals and the object × section contributions, the builder
+/// keeps the address and size of every symbol to work out gaps in
+/// [`MetricsBuilder::finish`].
+#[derive(Debug)]
+pub struct MetricsBuilder {
+    rules: Rules,
+    /// Sections and object files of the map taken in so far
+    seen_sections: usize,
+    seen_objects: usize,
+    section_ranges: Vec<SectionRange>,
+    sections: Vec<SectionMetrics>,
+    /// `(address, size)` of the sized symbols in each section
+    placed: Vec<Vec<(u64, u64)>>,
+    objects: Vec<ObjectMetrics>,
+    object_index_lookup: HashMap<u32, usize>,
+    crates: Vec<CrateMetrics>,
+    crate_index_lookup: HashMap<String, usize>,
+    contributions: HashMap<(u32, usize), ContributionMetrics>,
+    gaps: GapMetrics,
+    totals: GlobalTotals,
+}
 
-    let mut totals = GlobalTotals::default();
-    let mut symbol_metrics: Vec<SymbolMetrics> = Vec::with_capacity(map.symbols.len());
+impl MetricsBuilder {
+    /// Start aggregating symbols of `map`; only its sections and object files are used.
+    ///
+    /// Sections are classified with the built-in rules for the map's binary format.
+    pub fn new(map: &MapFile) -> Self {
+        Self::with_rules(map, &Rules::for_format(map.binary_format))
+    }
 
-    for symbol in &map.symbols {
-        let address = if let Some(parsed) = parse_hex_u64(&symbol.address) {
-            parsed
-        } else {
-            continue;
+    /// Like [`MetricsBuilder::new`], classifying sections with `rules`.
+    pub fn with_rules(map: &MapFile, rules: &Rules) -> Self {
+        let mut builder = MetricsBuilder {
+            rules: rules.clone(),
+            seen_sections: 0,
+            seen_objects: 0,
+            section_ranges: Vec::new(),
+            sections: Vec::new(),
+            placed: Vec::new(),
+            objects: Vec::new(),
+            object_index_lookup: HashMap::new(),
+            crates: Vec::new(),
+            crate_index_lookup: HashMap::new(),
+            contributions: HashMap::new(),
+            gaps: GapMetrics::default(),
+            totals: GlobalTotals::default(),
         };
-        let size = parse_hex_u64(&symbol.size).unwrap_or(0);
-        let file_index: i32 = symbol.file_index.parse().unwrap_or(-1);
-
-        let bucket = find_section(&section_ranges, address)
-            .map(|range| classify(&range.segment, &range.section))
-            .unwrap_or(Bucket::Other);
-
-        if let Some(&object_pos) = object_index_lookup.get(&file_index) {
-            let object_metrics = &mut objects[object_pos];
-            match bucket {
-                Bucket::Text => object_metrics.text += size,
-                Bucket::Data => object_metrics.data += size,
-                Bucket::Bss => object_metrics.bss += size,
-                Bucket::Other => object_metrics.other += size,
+        builder.update(map);
+        for symbol in &map.dead_stripped {
+            builder.add_dead_stripped(symbol);
+        }
+        builder
+    }
+
+    /// Take in the sections and object files added to `map` since the last call.
+    ///
+    /// A [`linkerland_parser::SymbolStream`] over an lld or mold map adds them
+    /// to its header as they are read, each before its first symbol, so call
+    /// this with the header before adding each symbol and once the stream ends.
+    pub fn update(&mut self, map: &MapFile) {
+        for object_file in map.object_files.iter().skip(self.seen_objects) {
+            self.object_index_lookup
+                .insert(object_file.index, self.objects.len());
+            self.objects.push(object_metrics(object_file));
+        }
+        self.seen_objects = self.seen_objects.max(map.object_files.len());
+        if let Some(new) = map.sections.get(self.seen_sections..) {
+            if !new.is_empty() {
+                self.insert_sections(new);
+            }
+            self.seen_sections = map.sections.len();
+        }
+    }
+
+    /// Classify `new` sections and merge them in by address, moving the
+    /// contributions and placed symbols of the sections already there along.
+    fn insert_sections(&mut self, new: &[Section]) {
+        let known = self.sections.len();
+        let mut sections: Vec<(usize, SectionMetrics)> = std::mem::take(&mut self.sections)
+            .into_iter()
+            .chain(
+                new.iter()
+                    .map(|section| section_metrics(section, &self.rules)),
+            )
+            .enumerate()
+            .collect();
+        sections.sort_by_key(|(_, section)| section.start);
+
+        let mut moved = vec![0; known];
+        let mut placed = std::mem::take(&mut self.placed);
+        self.placed = sections
+            .iter()
+            .enumerate()
+            .map(|(pos, &(old, _))| match moved.get_mut(old) {
+                Some(slot) => {
+                    *slot = pos;
+                    std::mem::take(&mut placed[old])
+                }
+                None => Vec::new(),
+            })
+            .collect();
+        if moved.iter().enumerate().any(|(old, &pos)| old != pos) {
+            self.contributions = std::mem::take(&mut self.contributions)
+                .into_values()
+                .map(|mut cell| {
+                    cell.section = moved[cell.section];
+                    ((cell.object_id, cell.section), cell)
+                })
+                .collect();
+        }
+
+        self.sections = sections.into_iter().map(|(_, section)| section).collect();
+        self.section_ranges = self
+            .sections
+            .iter()
+            .map(|section| SectionRange {
+                start: section.start,
+                end: section.end,
+                bucket: section.bucket.clone(),
+            })
+            .collect();
+    }
+
+    /// Add the size of a dead-stripped symbol to its object's and the global totals.
+    ///
+    /// [`MetricsBuilder::new`] already counts `map.dead_stripped`; this is for
+    /// symbols collected later, e.g. by a [`linkerland_parser::SymbolStream`].
+    pub fn add_dead_stripped(&mut self, symbol: &DeadSymbol) {
+        if let Some(&object_pos) = self.object_index_lookup.get(&symbol.file_index) {
+            self.objects[object_pos].dead_stripped += symbol.size;
+        }
+        self.totals.dead_stripped += symbol.size;
+    }
+
+    /// Classify `symbol` and add its size to the totals.
+    pub fn add(&mut self, symbol: &Symbol) -> SymbolMetrics {
+        let Symbol {
+            address,
+            size,
+            file_index,
+            kind,
+            ..
+        } = *symbol;
+
+        let section_pos = find_section(&self.section_ranges, address);
+        let bucket =
+            section_pos.map_or(Bucket::Other, |pos| self.section_ranges[pos].bucket.clone());
+        if let Some(pos) = section_pos {
+            if size > 0 {
+                self.placed[pos].push((address, size));
+            }
+            let section = &mut self.sections[pos];
+            section.symbol_size += size;
+            section.symbols += 1;
+            if section.largest_symbol.is_none() || size > section.largest_symbol_size {
+                section.largest_symbol = Some(symbol.name.to_string());
+                section.largest_symbol_size = size;
             }
-            object_metrics.total += size;
         }
 
-        match bucket {
-            Bucket::Text => totals.text += size,
-            Bucket::Data => totals.data += size,
-            Bucket::Bss => totals.bss += size,
-            Bucket::Other => totals.other += size,
+        let object_pos = self.object_index_lookup.get(&file_index).copied();
+        let crate_name = symbol_crate(&symbol.name)
+            .map(str::to_string)
+            .or_else(|| object_pos.and_then(|pos| self.objects[pos].crate_name.clone()));
+
+        if let Some(object_pos) = object_pos {
+            self.objects[object_pos].tally(&bucket, size);
+            if let Some(section) = section_pos {
+                let cell = self.contributions.entry((file_index, section)).or_insert(
+                    ContributionMetrics {
+                        object_id: file_index,
+                        section,
+                        size: 0,
+                        symbols: 0,
+                    },
+                );
+                cell.size += size;
+                cell.symbols += 1;
+            }
         }
-        totals.total += size;
+        self.totals.tally(&bucket, size);
+        *self.totals.by_kind.entry(kind).or_default() += size;
+        self.crate_metrics(crate_name.as_deref().unwrap_or(UNKNOWN_CRATE))
+            .tally(&bucket, size);
 
-        symbol_metrics.push(SymbolMetrics {
+        SymbolMetrics {
             address,
             size,
             file_index,
-            name: symbol.name.clone(),
+            name: symbol.name.to_string(),
+            demangled_name: None,
             bucket,
+            kind,
+            crate_name,
+        }
+    }
+
+    fn crate_metrics(&mut self, name: &str) -> &mut CrateMetrics {
+        let position = match self.crate_index_lookup.get(name) {
+            Some(&position) => position,
+            None => {
+                self.crates.push(CrateMetrics {
+                    name: name.to_string(),
+                    text: 0,
+                    rodata: 0,
+                    data: 0,
+                    bss: 0,
+                    other: 0,
+                    custom: BTreeMap::new(),
+                    total: 0,
+                });
+                self.crate_index_lookup
+                    .insert(name.to_string(), self.crates.len() - 1);
+                self.crates.len() - 1
+            }
+        };
+        &mut self.crates[position]
+    }
+
+    /// Finish aggregation, attaching whichever classified symbols the caller kept.
+    pub fn finish(mut self, symbols: Vec<SymbolMetrics>) -> Metrics {
+        self.account_gaps();
+        self.crates
+            .sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.name.cmp(&b.name)));
+        let mut contributions: Vec<ContributionMetrics> =
+            self.contributions.into_values().collect();
+        contributions.sort_by_key(|cell| (cell.object_id, cell.section));
+        Metrics {
+            crates: self.crates,
+            sections: self.sections,
+            contributions,
+            archives: build_archive_metrics(&self.objects),
+            objects: self.objects,
+            symbols,
+            gaps: self.gaps,
+            totals: self.totals,
+        }
+    }
+}
+
+impl MetricsBuilder {
+    /// Work out the padding and unattributed bytes of every section from the
+    /// symbols added to it.
+    ///
+    /// They are added to [`Metrics::gaps`] as [`PADDING`] and [`UNKNOWN`] objects
+    /// with one symbol per hole.
+    fn account_gaps(&mut self) {
+        let mut placed = std::mem::take(&mut self.placed);
+        let mut padding = pseudo_object(PADDING_OBJECT_ID, PADDING);
+        let mut unknown = pseudo_object(UNKNOWN_OBJECT_ID, UNKNOWN);
+        let mut contributions: HashMap<(u32, usize), ContributionMetrics> = HashMap::new();
+        for (pos, (section, placed)) in self.sections.iter_mut().zip(&mut placed).enumerate() {
+            let gaps = section_gaps(section.start, section.end, placed);
+            section.padding = gaps.padding;
+            section.unknown = gaps.unknown;
+            section.overlap = gaps.overlap;
+            self.totals.padding += gaps.padding;
+            self.totals.unknown += gaps.unknown;
+            self.totals.overlap += gaps.overlap;
+
+            for hole in gaps.holes {
+                let object = if hole.padding {
+                    &mut padding
+                } else {
+                    &mut unknown
+                };
+                object.tally(&section.bucket, hole.size);
+                let cell = contributions
+                    .entry((object.id, pos))
+                    .or_insert(ContributionMetrics {
+                        object_id: object.id,
+                        section: pos,
+                        size: 0,
+                        symbols: 0,
+                    });
+                cell.size += hole.size;
+                cell.symbols += 1;
+                self.gaps.symbols.push(SymbolMetrics {
+                    address: hole.address,
+                    size: hole.size,
+                    file_index: object.id,
+                    name: object.name.clone(),
+                    demangled_name: None,
+                    bucket: section.bucket.clone(),
+                    kind: SymbolKind::LinkerSynthesized,
+                    crate_name: None,
+                });
+            }
+        }
+        self.gaps.objects.extend(
+            [padding, unknown]
+                .into_iter()
+                .filter(|object| object.total > 0),
+        );
+        self.gaps.contributions = contributions.into_values().collect();
+        self.gaps
+            .contributions
+            .sort_by_key(|cell| (cell.object_id, cell.section));
+    }
+}
+
+/// An empty object standing in for bytes no input object contributed.
+fn pseudo_object(id: u32, name: &str) -> ObjectMetrics {
+    ObjectMetrics {
+        id,
+        path: name.to_string(),
+        archive: None,
+        name: name.to_string(),
+        kind: ObjectKind::LinkerSynthesized,
+        crate_name: None,
+        text: 0,
+        rodata: 0,
+        data: 0,
+        bss: 0,
+        other: 0,
+        custom: BTreeMap::new(),
+        total: 0,
+        dead_stripped: 0,
+    }
+}
+
+fn build_archive_metrics(objects: &[ObjectMetrics]) -> Vec<ArchiveMetrics> {
+    let mut archives: Vec<ArchiveMetrics> = Vec::new();
+    let mut archive_index_lookup: HashMap<&str, usize> = HashMap::new();
+    for object in objects {
+        let Some(path) = &object.archive else {
+            continue;
+        };
+        let position = *archive_index_lookup.entry(path).or_insert_with(|| {
+            archives.push(ArchiveMetrics {
+                path: path.clone(),
+                members: 0,
+                text: 0,
+                rodata: 0,
+                data: 0,
+                bss: 0,
+                other: 0,
+                custom: BTreeMap::new(),
+                total: 0,
+                dead_stripped: 0,
+            });
+            archives.len() - 1
         });
+        let archive = &mut archives[position];
+        archive.members += 1;
+        archive.text += object.text;
+        archive.rodata += object.rodata;
+        archive.data += object.data;
+        archive.bss += object.bss;
+        archive.other += object.other;
+        for (name, &bytes) in &object.custom {
+            *archive.custom.entry(name.clone()).or_default() += bytes;
+        }
+        archive.total += object.total;
+        archive.dead_stripped += object.dead_stripped;
     }
+    archives.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.path.cmp(&b.path)));
+    archives
+}
+
+/// Build aggregated metrics (per-object, per-archive, per-symbol classification, global totals).
+pub fn build_metrics(map: &MapFile) -> Metrics {
+    build_metrics_with_rules(map, &Rules::for_format(map.binary_format))
+}
+
+/// Like [`build_metrics`], classifying sections with `rules`.
+pub fn build_metrics_with_rules(map: &MapFile, rules: &Rules) -> Metrics {
+    build_metrics_where(map, rules, |_| true)
+}
+
+/// Like [`build_metrics_with_rules`], counting only the symbols `keep` accepts.
+pub fn build_metrics_where(
+    map: &MapFile,
+    rules: &Rules,
+    mut keep: impl FnMut(&Symbol) -> bool,
+) -> Metrics {
+    let mut builder = MetricsBuilder::with_rules(map, rules);
+    let symbols: Vec<SymbolMetrics> = map
+        .symbols
+        .iter()
+        .filter(|symbol| keep(symbol))
+        .map(|symbol| builder.add(symbol))
+        .collect();
+    builder.finish(symbols)
+}
 
-    Metrics {
-        objects,
-        symbols: symbol_metrics,
-        totals,
+/// Like [`build_metrics_where`], for the symbols read from `stream`.
+///
+/// Symbols are aggregated as they are read and not kept, so
+/// [`Metrics::symbols`] is empty. Stops at the first error in the map.
+pub fn stream_metrics_where(
+    stream: &mut SymbolStream,
+    rules: &Rules,
+    mut keep: impl FnMut(&Symbol) -> bool,
+) -> Result<Metrics, Error> {
+    let mut builder = MetricsBuilder::with_rules(stream.header(), rules);
+    while let Some(symbol) = stream.next() {
+        let symbol = symbol?;
+        builder.update(stream.header());
+        if keep(&symbol) {
+            builder.add(&symbol);
+        }
+    }
+    builder.update(stream.header());
+    for symbol in &stream.header().dead_stripped {
+        builder.add_dead_stripped(symbol);
     }
+    Ok(builder.finish(Vec::new()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use linkerland_parser::{BinaryFormat, MapFile, ObjectFile, Symbol};
+    use linkerland_parser::{BinaryFormat, MapDialect, MapFile, ObjectFile};
 
-    fn mk_map() -> MapFile {
+    fn mk_map() -> MapFile<'static> {
         MapFile {
             arch: "arm64".into(),
             target_path: "/tmp/app".into(),
             binary_format: BinaryFormat::MachO,
-            object_files: vec![ObjectFile {
-                index: 1,
-                path: "a.o".into(),
-            }],
+            object_files: vec![ObjectFile::new(1, "a.o")],
             sections: vec![Section {
-                address: "0x1000".into(),
-                size: "0x50".into(),
+                address: 0x1000,
+                size: 0x50,
                 segment: "__TEXT".into(),
                 section: "__text".into(),
+                ..Default::default()
             }],
+            memory_regions: vec![],
+            dialect: MapDialect::Ld64,
+            invalid_utf8: 0,
             symbols: vec![Symbol {
-                address: "0x1000".into(),
-                size: "0x10".into(),
-                file_index: "1".into(),
+                address: 0x1000,
+                size: 0x10,
+                file_index: 1,
+                kind: SymbolKind::Function,
                 name: "_foo".into(),
             }],
+            dead_stripped: vec![],
         }
     }
 
@@ -258,5 +825,380 @@ mod tests {
         assert_eq!(res.objects[0].text, 0x10);
         assert_eq!(res.totals.text, 0x10);
         assert_eq!(res.symbols[0].bucket, Bucket::Text);
+        assert_eq!(res.symbols[0].kind, SymbolKind::Function);
+        assert_eq!(res.totals.by_kind[&SymbolKind::Function], 0x10);
+    }
+
+    #[test]
+    fn sections_collect_their_symbols() {
+        let mut map = mk_map();
+        map.symbols.push(Symbol {
+            address: 0x1010,
+            size: 0x30,
+            file_index: 1,
+            kind: SymbolKind::Function,
+            name: "_bar".into(),
+        });
+        map.symbols.push(Symbol {
+            address: 0x3000,
+            size: 0x8,
+            file_index: 1,
+            kind: SymbolKind::Data,
+            name: "_outside".into(),
+        });
+        let res = build_metrics(&map);
+        let text = &res.sections[0];
+        assert_eq!((text.start, text.end, text.size), (0x1000, 0x1050, 0x50));
+        assert_eq!(text.bucket, Bucket::Text);
+        assert_eq!(text.symbol_size, 0x40);
+        assert_eq!(text.symbols, 2);
+        assert_eq!(text.largest_symbol.as_deref(), Some("_bar"));
+        assert_eq!(text.largest_symbol_size, 0x30);
+        assert_eq!((text.padding, text.unknown, text.overlap), (0, 0x10, 0));
+        assert_eq!(res.totals.unknown, 0x10);
+    }
+
+    #[test]
+    fn contributions_per_object_and_section() {
+        let mut map = mk_map();
+        map.object_files.push(ObjectFile::new(2, "b.o"));
+        map.sections.push(Section {
+            address: 0x2000,
+            size: 0x20,
+            segment: "__TEXT".into(),
+            section: "__gcc_except_tab".into(),
+            ..Default::default()
+        });
+        for (address, size, file_index) in [(0x1010, 0x20, 2), (0x2000, 0x8, 2), (0x2008, 0x4, 2)] {
+            map.symbols.push(Symbol {
+                address,
+                size,
+                file_index,
+                kind: SymbolKind::Function,
+                name: "_sym".into(),
+            });
+        }
+        let res = build_metrics(&map);
+        let cells: Vec<_> = res
+            .contributions
+            .iter()
+            .map(|cell| (cell.object_id, cell.section, cell.size, cell.symbols))
+            .collect();
+        assert_eq!(
+            cells,
+            vec![(1, 0, 0x10, 1), (2, 0, 0x20, 1), (2, 1, 0xC, 2)]
+        );
+        let gaps: Vec<_> = res
+            .gaps
+            .contributions
+            .iter()
+            .map(|cell| (cell.object_id, cell.section, cell.size, cell.symbols))
+            .collect();
+        assert_eq!(
+            gaps,
+            vec![
+                (PADDING_OBJECT_ID, 1, 0x14, 1),
+                (UNKNOWN_OBJECT_ID, 0, 0x20, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn build_where_skips_rejected_symbols() {
+        let mut map = mk_map();
+        map.symbols.push(Symbol {
+            address: 0x1010,
+            size: 0x30,
+            file_index: 1,
+            kind: SymbolKind::Data,
+            name: "_bar".into(),
+        });
+        let res = build_metrics_where(&map, &Rules::for_format(map.binary_format), |s| {
+            s.kind == SymbolKind::Data
+        });
+        assert_eq!(res.symbols[0].name, "_bar");
+        assert_eq!(res.totals.text, 0x30);
+        assert_eq!(res.sections[0].symbols, 1);
+    }
+
+    #[test]
+    fn gaps_explain_section_sizes() {
+        let mut map = mk_map();
+        map.sections.push(Section {
+            address: 0x2000,
+            size: 0x100,
+            segment: "__DATA".into(),
+            section: "__data".into(),
+            ..Default::default()
+        });
+        for (address, size) in [(0x1010, 0x2c), (0x1020, 0x10), (0x2000, 0x4), (0x2080, 0x8)] {
+            map.symbols.push(Symbol {
+                address,
+                size,
+                file_index: 1,
+                kind: SymbolKind::Data,
+                name: "_sym".into(),
+            });
+        }
+        let res = build_metrics(&map);
+        let totals = &res.totals;
+        let section_sizes: u64 = res.sections.iter().map(|section| section.size).sum();
+        assert_eq!(
+            totals.total + totals.padding + totals.unknown - totals.overlap,
+            section_sizes
+        );
+        assert_eq!(totals.overlap, 0x10);
+
+        // Pseudo-objects hold exactly the padding and unattributed bytes,
+        // apart from the objects of the map.
+        assert_eq!(res.objects.len(), 1);
+        assert_eq!(res.objects[0].total, totals.total);
+        let gap_totals: Vec<_> = res.gaps.objects.iter().map(|o| (o.id, o.total)).collect();
+        assert_eq!(
+            gap_totals,
+            [
+                (PADDING_OBJECT_ID, totals.padding),
+                (UNKNOWN_OBJECT_ID, totals.unknown)
+            ]
+        );
+
+        // One pseudo-symbol per hole, at the hole's address.
+        assert_eq!(res.symbols.len(), map.symbols.len());
+        let holes: Vec<_> = res
+            .gaps
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.address, s.size))
+            .collect();
+        assert_eq!(
+            holes,
+            [
+                (PADDING, 0x103c, 0x4),
+                (UNKNOWN, 0x1040, 0x10),
+                (PADDING, 0x2004, 0x3c),
+                (UNKNOWN, 0x2040, 0x40),
+                (PADDING, 0x2088, 0x38),
+                (UNKNOWN, 0x20c0, 0x40),
+            ]
+        );
+    }
+
+    #[test]
+    fn rodata_separated_from_text() {
+        let mut map = mk_map();
+        map.sections.push(Section {
+            address: 0x1050,
+            size: 0x20,
+            segment: "__TEXT".into(),
+            section: "__cstring".into(),
+            ..Default::default()
+        });
+        map.symbols.push(Symbol {
+            address: 0x1050,
+            size: 0x6,
+            file_index: 1,
+            kind: SymbolKind::LiteralCString,
+            name: "literal string: hello".into(),
+        });
+        let res = build_metrics(&map);
+        assert_eq!(res.symbols[1].bucket, Bucket::Rodata);
+        assert_eq!(res.objects[0].text, 0x10);
+        assert_eq!(res.objects[0].rodata, 0x6);
+        assert_eq!(res.totals.rodata, 0x6);
+        assert_eq!(res.totals.total, 0x16);
+    }
+
+    #[test]
+    fn custom_buckets_from_rules() {
+        let mut map = mk_map();
+        map.sections.push(Section {
+            address: 0x2000,
+            size: 0x10,
+            segment: "__DATA".into(),
+            section: "__thread_vars".into(),
+            ..Default::default()
+        });
+        map.symbols.push(Symbol {
+            address: 0x2000,
+            size: 0x8,
+            file_index: 1,
+            kind: SymbolKind::Data,
+            name: "_tls".into(),
+        });
+        let rules = Rules::from_toml(
+            "[[rule]]\nsection = \"__thread_*\"\nbucket = \"TLS\"",
+            map.binary_format,
+        )
+        .unwrap();
+        let res = build_metrics_with_rules(&map, &rules);
+        assert_eq!(res.symbols[1].bucket, Bucket::Custom("TLS".into()));
+        assert_eq!(res.objects[0].custom["TLS"], 0x8);
+        assert_eq!(res.objects[0].data, 0);
+        assert_eq!(res.objects[0].total, 0x18);
+        assert_eq!(res.totals.custom["TLS"], 0x8);
+        assert_eq!(res.crates[0].custom["TLS"], 0x8);
+    }
+
+    #[test]
+    fn dead_stripped_per_object() {
+        let mut map = mk_map();
+        map.object_files.push(ObjectFile::new(2, "unused.o"));
+        map.dead_stripped = vec![
+            DeadSymbol {
+                size: 0x8,
+                file_index: 1,
+                name: "_bar".into(),
+            },
+            DeadSymbol {
+                size: 0x20,
+                file_index: 2,
+                name: "_baz".into(),
+            },
+        ];
+        let res = build_metrics(&map);
+        assert_eq!(res.objects[0].dead_stripped, 0x8);
+        assert!(!res.objects[0].only_dead_code());
+        assert_eq!(res.objects[1].dead_stripped, 0x20);
+        assert!(res.objects[1].only_dead_code());
+        assert_eq!(res.totals.dead_stripped, 0x28);
+        assert_eq!(res.totals.total, 0x10);
+    }
+
+    #[test]
+    fn archives_roll_up_members() {
+        let mut map = mk_map();
+        map.object_files = vec![
+            ObjectFile::new(1, "libfoo.a[3](a.o)"),
+            ObjectFile::new(2, "libfoo.a[4](b.o)"),
+            ObjectFile::new(3, "main.o"),
+        ];
+        map.symbols.push(Symbol {
+            address: 0x1010,
+            size: 0x20,
+            file_index: 2,
+            kind: SymbolKind::Function,
+            name: "_bar".into(),
+        });
+        let res = build_metrics(&map);
+        assert_eq!(res.objects[0].name, "a.o");
+        assert_eq!(res.objects[0].archive.as_deref(), Some("libfoo.a"));
+        assert_eq!(res.archives.len(), 1);
+        assert_eq!(res.archives[0].path, "libfoo.a");
+        assert_eq!(res.archives[0].members, 2);
+        assert_eq!(res.archives[0].text, 0x30);
+    }
+
+    #[test]
+    fn crates_from_symbols_then_objects() {
+        let mut map = mk_map();
+        map.object_files = vec![ObjectFile::new(
+            1,
+            "libapp.a[2](gimli-1ab316f099134a82.gimli.86b73ce1-cgu.2.rcgu.o)",
+        )];
+        map.symbols = vec![
+            Symbol {
+                address: 0x1000,
+                size: 0x10,
+                file_index: 1,
+                kind: SymbolKind::Function,
+                name: "__ZN4core3fmt5write17h0123456789abcdefE".into(),
+            },
+            Symbol {
+                address: 0x1010,
+                size: 0x8,
+                file_index: 1,
+                kind: SymbolKind::Anonymous,
+                name: "anon".into(),
+            },
+        ];
+        let res = build_metrics(&map);
+        assert_eq!(res.objects[0].crate_name.as_deref(), Some("gimli"));
+        assert_eq!(res.symbols[0].crate_name.as_deref(), Some("core"));
+        assert_eq!(res.symbols[1].crate_name.as_deref(), Some("gimli"));
+        assert_eq!(res.crates[0].name, "core");
+        assert_eq!(res.crates[0].text, 0x10);
+        assert_eq!(res.crates[1].name, "gimli");
+        assert_eq!(res.crates[1].total, 0x8);
+    }
+
+    #[test]
+    fn demangling_is_on_request() {
+        let mut map = mk_map();
+        map.symbols[0].name = "__ZN4core3fmt5write17h0123456789abcdefE".into();
+        let mut res = build_metrics(&map);
+        assert_eq!(res.symbols[0].demangled_name, None);
+        res.demangle_symbols(false);
+        assert_eq!(
+            res.symbols[0].demangled_name.as_deref(),
+            Some("core::fmt::write::h0123456789abcdef")
+        );
+        res.demangle_symbols(true);
+        assert_eq!(
+            res.symbols[0].demangled_name.as_deref(),
+            Some("core::fmt::write")
+        );
+    }
+
+    #[test]
+    fn builder_keeps_totals_without_symbols() {
+        let map = mk_map();
+        let mut builder = MetricsBuilder::new(&map);
+        for symbol in &map.symbols {
+            builder.add(symbol);
+        }
+        let res = builder.finish(Vec::new());
+        assert!(res.symbols.is_empty());
+        assert_eq!(res.objects[0].text, 0x10);
+        assert_eq!(res.totals.total, 0x10);
+    }
+
+    #[test]
+    fn streamed_metrics_match_parsed() {
+        for fixture in ["linker.map", "dead_strip.map", "lld.map", "mold.map"] {
+            let path = std::path::Path::new("../parser/tests/fixtures").join(fixture);
+            let mut parsed = build_metrics(&linkerland_parser::parse(&path).unwrap());
+            parsed.symbols.clear();
+
+            let mut stream = linkerland_parser::stream(&path).unwrap();
+            let rules = Rules::for_format(stream.header().binary_format);
+            let streamed = stream_metrics_where(&mut stream, &rules, |_| true).unwrap();
+            assert_eq!(format!("{streamed:?}"), format!("{parsed:?}"), "{fixture}");
+        }
+    }
+
+    #[test]
+    fn builder_takes_in_sections_as_they_arrive() {
+        let mut map = mk_map();
+        let text = map.sections.remove(0);
+        map.sections.push(Section {
+            address: 0x2000,
+            size: 0x10,
+            segment: "__DATA".into(),
+            section: "__data".into(),
+            ..Default::default()
+        });
+        let data = Symbol {
+            address: 0x2000,
+            size: 0x10,
+            file_index: 1,
+            ..map.symbols[0].clone()
+        };
+        let mut builder = MetricsBuilder::new(&map);
+        builder.add(&data);
+        map.sections.push(text);
+        builder.update(&map);
+        let symbol = builder.add(&map.symbols[0]);
+        assert_eq!(symbol.bucket, Bucket::Text);
+
+        let res = builder.finish(Vec::new());
+        assert_eq!(res.sections[0].section, "__text");
+        assert_eq!(
+            res.contributions
+                .iter()
+                .map(|cell| (cell.section, cell.size))
+                .collect::<Vec<_>>(),
+            [(0, 0x10), (1, 0x10)]
+        );
+        assert_eq!(res.sections[1].padding + res.sections[1].unknown, 0);
     }
 }
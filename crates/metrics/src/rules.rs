@@ -0,0 +1,430 @@
+//! Rules that map output sections to [`Bucket`]s.
+//!
+//! Each rule matches a segment and/or section glob and names a bucket; the
+//! first matching rule wins and unmatched sections count as [`Bucket::Other`].
+//! Built-in presets cover Mach-O, ELF and PE; read-only data such as
+//! `__TEXT,__const`, `.rodata` and unwind tables goes to [`Bucket::Rodata`].
+//! A TOML file can put its own rules, including extra buckets such as `TLS`
+//! or `DEBUG`, in front of them:
+//!
+//! ```toml
+//! # Rules below take precedence over the preset ("macho", "elf", "pe" or "none").
+//! # Without `preset`, the one matching the map's binary format is used.
+//! preset = "elf"
+//!
+//! [[rule]]
+//! section = ".tdata*"
+//! bucket = "TLS"
+//!
+//! [[rule]]
+//! segment = "__DWARF"
+//! bucket = "DEBUG"
+//! ```
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use globset::{Glob, GlobMatcher};
+use linkerland_parser::BinaryFormat;
+use serde::Deserialize;
+
+use crate::Bucket;
+
+/// Built-in rule sets, one per binary format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    MachO,
+    Elf,
+    Pe,
+}
+
+impl Preset {
+    /// `(segment, section, bucket)` globs, in order of precedence.
+    fn rules(self) -> &'static [(&'static str, &'static str, &'static str)] {
+        match self {
+            Preset::MachO => &[
+                ("*", "__bss", "bss"),
+                ("*", "__bss_coal", "bss"),
+                ("*", "__common", "bss"),
+                ("*", "__thread_bss", "bss"),
+                ("__TEXT", "__const", "rodata"),
+                ("__TEXT", "__cstring", "rodata"),
+                ("__TEXT", "__ustring", "rodata"),
+                ("__TEXT", "__literal*", "rodata"),
+                ("__TEXT", "__eh_frame", "rodata"),
+                ("__TEXT", "__gcc_except_tab", "rodata"),
+                ("__TEXT", "__unwind_info", "rodata"),
+                ("__TEXT", "*", "text"),
+                ("__DATA*", "*", "data"),
+                ("__AUTH*", "*", "data"),
+                ("*", "__data", "data"),
+                ("*", "__const", "data"),
+                ("*", "__got", "data"),
+                ("*", "__mod_init_func", "data"),
+                ("*", "__cstring", "data"),
+                ("*", "__const_coal", "data"),
+            ],
+            Preset::Elf => &[
+                ("*", ".text*", "text"),
+                ("*", ".init", "text"),
+                ("*", ".fini", "text"),
+                ("*", ".plt*", "text"),
+                ("*", ".iplt", "text"),
+                ("*", ".rodata*", "rodata"),
+                ("*", ".srodata*", "rodata"),
+                ("*", ".const", "rodata"),
+                ("*", ".eh_frame*", "rodata"),
+                ("*", ".gcc_except_table*", "rodata"),
+                ("*", ".ARM.exidx*", "rodata"),
+                ("*", ".ARM.extab*", "rodata"),
+                ("*", ".isr_vector", "rodata"),
+                ("*", ".intvec*", "rodata"),
+                ("*", ".interp", "rodata"),
+                ("*", ".note*", "rodata"),
+                ("*", ".hash", "rodata"),
+                ("*", ".gnu.hash", "rodata"),
+                ("*", ".gnu.version*", "rodata"),
+                ("*", ".dynsym", "rodata"),
+                ("*", ".dynstr", "rodata"),
+                ("*", ".rel.*", "rodata"),
+                ("*", ".rela.*", "rodata"),
+                ("*", ".bss*", "bss"),
+                ("*", ".sbss*", "bss"),
+                ("*", ".tbss*", "bss"),
+                ("*", "COMMON", "bss"),
+                // Includes `.data.rel.ro`, which is written by the dynamic loader.
+                ("*", ".data*", "data"),
+                ("*", ".sdata*", "data"),
+                ("*", ".tdata*", "data"),
+                ("*", ".got*", "data"),
+                ("*", ".init_array*", "data"),
+                ("*", ".fini_array*", "data"),
+                ("*", ".preinit_array*", "data"),
+                ("*", ".ctors*", "data"),
+                ("*", ".dtors*", "data"),
+                ("*", ".dynamic", "data"),
+            ],
+            Preset::Pe => &[
+                ("*", ".text*", "text"),
+                ("*", ".rdata*", "rodata"),
+                ("*", ".xdata*", "rodata"),
+                ("*", ".pdata*", "rodata"),
+                ("*", ".edata*", "rodata"),
+                ("*", ".bss*", "bss"),
+                ("*", ".data*", "data"),
+                ("*", ".idata*", "data"),
+                ("*", ".tls*", "data"),
+                ("*", ".CRT*", "data"),
+            ],
+        }
+    }
+
+    /// Presets for maps of `format`; all of them when the format is unknown.
+    fn for_format(format: BinaryFormat) -> &'static [Preset] {
+        match format {
+            BinaryFormat::MachO => &[Preset::MachO],
+            BinaryFormat::Elf => &[Preset::Elf],
+            BinaryFormat::Pe => &[Preset::Pe],
+            BinaryFormat::Unknown => &[Preset::MachO, Preset::Elf, Preset::Pe],
+        }
+    }
+}
+
+/// Why a rules file could not be loaded.
+#[derive(Debug)]
+pub enum RulesError {
+    /// The rules file cannot be read
+    Io { path: PathBuf, source: io::Error },
+    /// The file is not valid TOML or has unknown keys
+    Toml(toml::de::Error),
+    /// A `segment` or `section` pattern is not a valid glob
+    Glob {
+        pattern: String,
+        source: globset::Error,
+    },
+    /// Rule `index` (0-based) matches neither a segment nor a section
+    EmptyRule { index: usize },
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            RulesError::Toml(error) => write!(f, "invalid rules: {error}"),
+            RulesError::Glob { pattern, source } => {
+                write!(f, "invalid pattern `{pattern}`: {source}")
+            }
+            RulesError::EmptyRule { index } => write!(
+                f,
+                "rule {} needs a `segment` or `section` pattern",
+                index + 1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RulesError::Io { source, .. } => Some(source),
+            RulesError::Toml(error) => Some(error),
+            RulesError::Glob { source, .. } => Some(source),
+            RulesError::EmptyRule { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    segment: GlobMatcher,
+    section: GlobMatcher,
+    bucket: Bucket,
+}
+
+impl Rule {
+    fn new(segment: &str, section: &str, bucket: Bucket) -> Result<Self, RulesError> {
+        Ok(Rule {
+            segment: matcher(segment)?,
+            section: matcher(section)?,
+            bucket,
+        })
+    }
+}
+
+fn matcher(pattern: &str) -> Result<GlobMatcher, RulesError> {
+    Glob::new(pattern)
+        .map(|glob| glob.compile_matcher())
+        .map_err(|source| RulesError::Glob {
+            pattern: pattern.to_string(),
+            source,
+        })
+}
+
+/// Contents of a rules file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    preset: Option<PresetName>,
+    #[serde(default, rename = "rule")]
+    rules: Vec<RuleEntry>,
+}
+
+/// The `preset` of a rules file; `none` keeps only the file's own rules.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum PresetName {
+    MachO,
+    Elf,
+    Pe,
+    None,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleEntry {
+    segment: Option<String>,
+    section: Option<String>,
+    bucket: String,
+}
+
+/// An ordered list of section classification rules.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    /// The built-in rules of `preset`.
+    pub fn preset(preset: Preset) -> Self {
+        let mut rules = Rules { rules: Vec::new() };
+        rules.push_preset(preset);
+        rules
+    }
+
+    /// The built-in rules for maps of `format`.
+    pub fn for_format(format: BinaryFormat) -> Self {
+        let mut rules = Rules { rules: Vec::new() };
+        for &preset in Preset::for_format(format) {
+            rules.push_preset(preset);
+        }
+        rules
+    }
+
+    /// Rules from the TOML `text` of a rules file, for maps of `format`.
+    pub fn from_toml(text: &str, format: BinaryFormat) -> Result<Self, RulesError> {
+        let file: RulesFile = toml::from_str(text).map_err(RulesError::Toml)?;
+        let mut rules = Rules { rules: Vec::new() };
+        for (index, entry) in file.rules.iter().enumerate() {
+            if entry.segment.is_none() && entry.section.is_none() {
+                return Err(RulesError::EmptyRule { index });
+            }
+            rules.rules.push(Rule::new(
+                entry.segment.as_deref().unwrap_or("*"),
+                entry.section.as_deref().unwrap_or("*"),
+                Bucket::from_name(&entry.bucket),
+            )?);
+        }
+        let presets: &[Preset] = match file.preset {
+            Some(PresetName::MachO) => &[Preset::MachO],
+            Some(PresetName::Elf) => &[Preset::Elf],
+            Some(PresetName::Pe) => &[Preset::Pe],
+            Some(PresetName::None) => &[],
+            None => Preset::for_format(format),
+        };
+        for &preset in presets {
+            rules.push_preset(preset);
+        }
+        Ok(rules)
+    }
+
+    /// Rules from the TOML file at `path`, for maps of `format`.
+    pub fn load(path: &Path, format: BinaryFormat) -> Result<Self, RulesError> {
+        let text = std::fs::read_to_string(path).map_err(|source| RulesError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_toml(&text, format)
+    }
+
+    fn push_preset(&mut self, preset: Preset) {
+        for &(segment, section, bucket) in preset.rules() {
+            let rule = Rule::new(segment, section, Bucket::from_name(bucket))
+                .expect("built-in patterns are valid globs");
+            self.rules.push(rule);
+        }
+    }
+
+    /// The bucket of the first rule matching `segment` and `section`.
+    pub fn classify(&self, segment: &str, section: &str) -> Bucket {
+        self.rules
+            .iter()
+            .find(|rule| rule.segment.is_match(segment) && rule.section.is_match(section))
+            .map_or(Bucket::Other, |rule| rule.bucket.clone())
+    }
+
+    /// Names of the user-defined buckets, in the order their first rule appears.
+    pub fn custom_buckets(&self) -> Vec<Arc<str>> {
+        let mut names: Vec<Arc<str>> = Vec::new();
+        for rule in &self.rules {
+            if let Bucket::Custom(name) = &rule.bucket {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_macho_preset() {
+        let rules = Rules::preset(Preset::MachO);
+        assert_eq!(rules.classify("__TEXT", "__text"), Bucket::Text);
+        assert_eq!(rules.classify("__TEXT", "__const"), Bucket::Rodata);
+        assert_eq!(rules.classify("__TEXT", "__cstring"), Bucket::Rodata);
+        assert_eq!(rules.classify("__TEXT", "__stubs"), Bucket::Text);
+        // Unwind and exception tables are read-only data, as on ELF, not code.
+        assert_eq!(rules.classify("__TEXT", "__eh_frame"), Bucket::Rodata);
+        assert_eq!(rules.classify("__TEXT", "__gcc_except_tab"), Bucket::Rodata);
+        assert_eq!(rules.classify("__TEXT", "__unwind_info"), Bucket::Rodata);
+        assert_eq!(rules.classify("__DATA_CONST", "__const"), Bucket::Data);
+        assert_eq!(rules.classify("__DATA", "__thread_bss"), Bucket::Bss);
+        assert_eq!(rules.classify("__DATA", "__bss"), Bucket::Bss);
+        assert_eq!(rules.classify("__LINKEDIT", "__linkedit"), Bucket::Other);
+    }
+
+    #[test]
+    fn test_elf_and_pe_presets() {
+        let elf = Rules::preset(Preset::Elf);
+        assert_eq!(elf.classify(".text", ".text.main"), Bucket::Text);
+        assert_eq!(elf.classify("RW_IRAM1", ".bss"), Bucket::Bss);
+        let cases = [
+            (".rodata.str1.1", Bucket::Rodata),
+            (".eh_frame", Bucket::Rodata),
+            (".gcc_except_table", Bucket::Rodata),
+            (".data.rel.ro", Bucket::Data),
+            (".init_array", Bucket::Data),
+            (".tdata", Bucket::Data),
+            (".tbss", Bucket::Bss),
+        ];
+        for (section, bucket) in cases {
+            assert_eq!(elf.classify(section, section), bucket, "{section}");
+        }
+        assert_eq!(elf.classify(".debug_info", ".debug_info"), Bucket::Other);
+
+        let pe = Rules::preset(Preset::Pe);
+        assert_eq!(pe.classify(".text", ".text$mn"), Bucket::Text);
+        assert_eq!(pe.classify(".rdata", ".rdata"), Bucket::Rodata);
+        assert_eq!(pe.classify(".data", ".data"), Bucket::Data);
+    }
+
+    #[test]
+    fn test_toml_rules_take_precedence() {
+        let rules = Rules::from_toml(
+            r#"
+            [[rule]]
+            section = ".tdata*"
+            bucket = "TLS"
+
+            [[rule]]
+            section = ".debug_*"
+            bucket = "DEBUG"
+
+            [[rule]]
+            section = ".data.rel.ro*"
+            bucket = "text"
+            "#,
+            BinaryFormat::Elf,
+        )
+        .unwrap();
+        assert_eq!(
+            rules.classify(".tdata", ".tdata"),
+            Bucket::Custom("TLS".into())
+        );
+        assert_eq!(
+            rules.classify(".debug_info", ".debug_info"),
+            Bucket::Custom("DEBUG".into())
+        );
+        assert_eq!(rules.classify(".data", ".data.rel.ro"), Bucket::Text);
+        assert_eq!(rules.classify(".data", ".data"), Bucket::Data);
+        assert_eq!(
+            rules.custom_buckets(),
+            [Arc::from("TLS"), Arc::from("DEBUG")]
+        );
+    }
+
+    #[test]
+    fn test_toml_presets() {
+        let rules = Rules::from_toml("preset = \"none\"", BinaryFormat::Elf).unwrap();
+        assert_eq!(rules.classify(".text", ".text"), Bucket::Other);
+
+        let rules = Rules::from_toml("preset = \"macho\"", BinaryFormat::Elf).unwrap();
+        assert_eq!(rules.classify("__TEXT", "__text"), Bucket::Text);
+        assert_eq!(rules.classify(".text", ".text"), Bucket::Other);
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        assert!(matches!(
+            Rules::from_toml("[[rule]]\nbucket = \"TLS\"", BinaryFormat::Elf),
+            Err(RulesError::EmptyRule { index: 0 })
+        ));
+        assert!(matches!(
+            Rules::from_toml(
+                "[[rule]]\nsection = \"[\"\nbucket = \"TLS\"",
+                BinaryFormat::Elf
+            ),
+            Err(RulesError::Glob { .. })
+        ));
+        assert!(matches!(
+            Rules::from_toml("preset = \"coff\"", BinaryFormat::Elf),
+            Err(RulesError::Toml(_))
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+//! Arm Compiler `armlink --map` files (with `--symbols`).
+//!
+//! Two blocks feed the model: the `Image Symbol Table`, which lists every
+//! symbol with its value, type and decimal size, and the `Memory Map of the
+//! image`, which nests execution regions inside load regions and lists the
+//! input sections placed in each:
+//!
+//! ```text
+//!   Load Region LR_IROM1 (Base: 0x08000000, Size: 0x00000220, Max: 0x00080000, ABSOLUTE)
+//!     Execution Region ER_IROM1 (Exec base: 0x08000000, Load base: 0x08000000, ...)
+//!     Exec Addr    Load Addr    Size         Type   Attr      Idx    E Section Name        Object
+//!     0x08000200   0x08000200   0x00000028   Code   RO            5    .text.main          main.o
+//! ```
+//!
+//! armlink has no output sections, so each input section row becomes a
+//! [`Section`] whose segment is its execution region.
+
+use std::borrow::Cow;
+
+use winnow::Result as ParserResult;
+
+use crate::{
+    attach_symbols, next_line, parse_hex, BinaryFormat, InputSection, MapDialect, MapFile,
+    ObjectTable, Section,
+};
+
+const SYMBOL_TABLE: &str = "Image Symbol Table";
+const MEMORY_MAP: &str = "Memory Map of the image";
+
+/// Returns true if the head carries the armlink banner or one of its block titles.
+pub(crate) fn sniff(head: &str) -> bool {
+    head.lines().map(str::trim).any(|line| {
+        line.starts_with("Component: ARM Compiler")
+            || line.starts_with("Component: Arm Compiler")
+            || line == SYMBOL_TABLE
+            || line == MEMORY_MAP
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    Other,
+    Symbols,
+    MemoryMap,
+}
+
+/// One input section row of an execution region.
+#[derive(Debug)]
+struct RegionRow<'i> {
+    exec_address: u64,
+    load_address: Option<u64>,
+    size: u64,
+    section: &'i str,
+    object: &'i str,
+}
+
+/// Parse an `Image Symbol Table` row (e.g. `main  0x08000201  Thumb Code  40  main.o(.text.main)`).
+///
+/// Returns the address (with the Thumb bit cleared), size and name of code and
+/// data symbols; section, number and absolute symbols are skipped.
+fn symbol_row(line: &str) -> Option<(u64, Option<u64>, &str)> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let (&name, rest) = tokens.split_first()?;
+    let value = rest.first()?.strip_prefix("0x")?;
+    let value = u64::from_str_radix(value, 16).ok()?;
+    let size_position = rest
+        .iter()
+        .rposition(|token| token.bytes().all(|b| b.is_ascii_digit()))?;
+    let kind = rest.get(1..size_position)?;
+    if !kind.iter().any(|&word| word == "Code" || word == "Data") {
+        return None;
+    }
+    let size: u64 = rest[size_position].parse().ok()?;
+    let address = if kind.contains(&"Thumb") {
+        value & !1
+    } else {
+        value
+    };
+    Some((address, (size > 0).then_some(size), name))
+}
+
+/// Parse an execution region row (e.g. `0x20000004  -  0x0000000c  Zero  RW  7  .bss  main.o`).
+fn region_row(line: &str) -> Option<RegionRow<'_>> {
+    let mut tokens = line.split_whitespace();
+    let exec_address = parse_hex(tokens.next()?.strip_prefix("0x")?)?;
+    let load_address = tokens.next()?;
+    let size = parse_hex(tokens.next()?.strip_prefix("0x")?)?;
+    let _kind = tokens.next()?;
+    let _attr = tokens.next()?;
+    let index = tokens.next()?;
+    if !index.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let mut section = tokens.next()?;
+    if section == "*" {
+        // Entry point marker
+        section = tokens.next()?;
+    }
+    let object = tokens.next().unwrap_or("");
+
+    Some(RegionRow {
+        exec_address,
+        load_address: load_address.strip_prefix("0x").and_then(parse_hex),
+        size,
+        section,
+        object,
+    })
+}
+
+/// Region name following a `Load Region` / `Execution Region` title.
+fn region_name<'i>(line: &'i str, title: &str) -> Option<&'i str> {
+    line.trim_start()
+        .strip_prefix(title)?
+        .split_whitespace()
+        .next()
+}
+
+/// Parse an armlink map into the common [`MapFile`] model.
+pub(crate) fn parse<'i>(input: &mut &'i str) -> ParserResult<MapFile<'i>> {
+    let mut objects = ObjectTable::default();
+    let mut sections = Vec::new();
+    let mut input_sections = Vec::new();
+    let mut symbols = Vec::new();
+    let mut block = Block::Other;
+    let mut load_region = None;
+    let mut execution_region = None;
+
+    while let Some(line) = next_line(input) {
+        let trimmed = line.trim();
+        if trimmed.starts_with("====") {
+            block = Block::Other;
+            continue;
+        }
+        match trimmed {
+            SYMBOL_TABLE => block = Block::Symbols,
+            MEMORY_MAP => block = Block::MemoryMap,
+            _ if block == Block::Symbols => symbols.extend(symbol_row(line)),
+            _ if block == Block::MemoryMap => {
+                if let Some(name) = region_name(line, "Load Region ") {
+                    load_region = Some(name);
+                } else if let Some(name) = region_name(line, "Execution Region ") {
+                    execution_region = Some(name);
+                } else if let Some(row) = region_row(line) {
+                    let region = execution_region.unwrap_or_default();
+                    sections.push(Section {
+                        address: row.exec_address,
+                        size: row.size,
+                        segment: Cow::Borrowed(region),
+                        section: Cow::Borrowed(row.section),
+                        load_address: row.load_address.filter(|&load| load != row.exec_address),
+                        alignment: None,
+                        load_region: load_region.map(Cow::Borrowed),
+                        execution_region: execution_region.map(Cow::Borrowed),
+                    });
+                    input_sections.push(InputSection {
+                        name: row.section,
+                        address: row.exec_address,
+                        size: row.size,
+                        file_index: objects.intern(row.object),
+                        symbols: Vec::new(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    attach_symbols(&mut input_sections, symbols);
+    let mut map_symbols = Vec::new();
+    for section in input_sections {
+        section.push_symbols(&mut map_symbols);
+    }
+
+    Ok(MapFile {
+        arch: "arm".into(),
+        object_files: objects.into_files(),
+        target_path: "".into(),
+        symbols: map_symbols,
+        dead_stripped: Vec::new(),
+        sections,
+        binary_format: BinaryFormat::Elf,
+        memory_regions: Vec::new(),
+        dialect: MapDialect::Armlink,
+        invalid_utf8: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        assert!(sniff(
+            "Component: ARM Compiler 5.06 update 6 (build 750) Tool: armlink [4d35ed]\n"
+        ));
+        assert!(!sniff("Linker script and memory map\n"));
+    }
+
+    #[test]
+    fn test_symbol_row_clears_thumb_bit() {
+        let row = symbol_row(
+            "    main                                     0x08000201   Thumb Code    40  main.o(.text.main)",
+        );
+        assert_eq!(row, Some((0x0800_0200, Some(40), "main")));
+    }
+
+    #[test]
+    fn test_symbol_row_skips_sections_and_numbers() {
+        assert_eq!(
+            symbol_row("    .text        0x00000100   Section        0  main.o(.text)"),
+            None
+        );
+        assert_eq!(
+            symbol_row("    ../Source/main.c   0x00000000   Number         0  main.o ABSOLUTE"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_region_row() {
+        let row = region_row(
+            "    0x08000130   0x08000130   0x00000008   Code   RO          230  * !!!main             c_w.l(__main.o)",
+        )
+        .unwrap();
+        assert_eq!(row.exec_address, 0x0800_0130);
+        assert_eq!(row.size, 8);
+        assert_eq!(row.section, "!!!main");
+        assert_eq!(row.object, "c_w.l(__main.o)");
+
+        let row = region_row(
+            "    0x20000004        -       0x0000000c   Zero   RW            7    .bss                main.o",
+        )
+        .unwrap();
+        assert_eq!(row.load_address, None);
+        assert_eq!(row.section, ".bss");
+    }
+}
@@ -0,0 +1,188 @@
+//! Transparent decompression of archived maps (`app.map.gz`, `app.map.zst`, `app.map.xz`).
+//!
+//! The format is recognized from the leading magic bytes rather than the file
+//! extension, so compressed maps piped through stdin are handled too. Each
+//! codec sits behind a cargo feature of the same name (all enabled by default).
+
+use std::io::{self, BufRead, Read};
+use std::path::Path;
+
+use crate::Error;
+
+/// Length of the longest magic number (xz).
+const MAGIC_LEN: usize = 6;
+
+/// Compression formats recognized by their magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Compression {
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Compression {
+    /// Identify the compression format from the leading bytes, if any.
+    pub(crate) fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0x1F, 0x8B]) {
+            Some(Compression::Gzip)
+        } else if bytes.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Some(Compression::Zstd)
+        } else if bytes.starts_with(&[0xFD, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else {
+            None
+        }
+    }
+
+    /// Name of the cargo feature (and codec) handling this format.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Xz => "xz",
+        }
+    }
+}
+
+/// Returns true if the file at `path` starts with a known magic number.
+pub(crate) fn is_compressed_file(path: &Path) -> bool {
+    let mut magic = Vec::with_capacity(MAGIC_LEN);
+    std::fs::File::open(path)
+        .and_then(|file| file.take(MAGIC_LEN as u64).read_to_end(&mut magic))
+        .is_ok_and(|_| Compression::detect(&magic).is_some())
+}
+
+/// Decompress `bytes` if they start with a known magic number; plain maps pass through.
+pub(crate) fn decompress(name: &Path, bytes: Vec<u8>) -> Result<Vec<u8>, Error> {
+    if Compression::detect(&bytes).is_none() {
+        return Ok(bytes);
+    }
+    let mut out = Vec::new();
+    reader(name, &bytes[..])?
+        .read_to_end(&mut out)
+        .map_err(|e| Error::io(name, e))?;
+    Ok(out)
+}
+
+/// Wrap `reader` in a decoder if its leading bytes match a known magic number.
+///
+/// Decompresses incrementally, so [`crate::SymbolStream`] never holds the whole map.
+pub(crate) fn reader<'r>(
+    name: &Path,
+    mut reader: impl BufRead + 'r,
+) -> Result<Box<dyn BufRead + 'r>, Error> {
+    let mut magic = Vec::with_capacity(MAGIC_LEN);
+    (&mut reader)
+        .take(MAGIC_LEN as u64)
+        .read_to_end(&mut magic)
+        .map_err(|e| Error::io(name, e))?;
+    let compression = Compression::detect(&magic);
+    let reader = io::Cursor::new(magic).chain(reader);
+    match compression {
+        None => Ok(Box::new(reader)),
+        #[cfg(feature = "gzip")]
+        Some(Compression::Gzip) => Ok(Box::new(io::BufReader::new(
+            flate2::read::MultiGzDecoder::new(reader),
+        ))),
+        #[cfg(feature = "zstd")]
+        Some(Compression::Zstd) => {
+            let decoder =
+                zstd::stream::read::Decoder::with_buffer(reader).map_err(|e| Error::io(name, e))?;
+            Ok(Box::new(io::BufReader::new(decoder)))
+        }
+        #[cfg(feature = "xz")]
+        Some(Compression::Xz) => Ok(Box::new(io::BufReader::new(
+            xz2::read::XzDecoder::new_multi_decoder(reader),
+        ))),
+        #[allow(unreachable_patterns)]
+        Some(compression) => Err(Error::UnsupportedCompression {
+            path: name.to_path_buf(),
+            compression: compression.as_str(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &[u8] = b"# Path: /target/debug/app\n# Arch: arm64\n";
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Compression::detect(MAP), None);
+        assert_eq!(
+            Compression::detect(&[0x1F, 0x8B, 0x08]),
+            Some(Compression::Gzip)
+        );
+        assert_eq!(
+            Compression::detect(&[0x28, 0xB5, 0x2F, 0xFD, 0x00]),
+            Some(Compression::Zstd)
+        );
+        assert_eq!(
+            Compression::detect(b"\xFD7zXZ\x00\x00"),
+            Some(Compression::Xz)
+        );
+    }
+
+    #[test]
+    fn test_plain_passes_through() {
+        let bytes = decompress(Path::new("app.map"), MAP.to_vec()).unwrap();
+        assert_eq!(bytes, MAP);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(MAP).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let bytes = decompress(Path::new("app.map.gz"), compressed).unwrap();
+        assert_eq!(bytes, MAP);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd() {
+        let compressed = zstd::encode_all(MAP, 0).unwrap();
+        let bytes = decompress(Path::new("app.map.zst"), compressed).unwrap();
+        assert_eq!(bytes, MAP);
+    }
+
+    #[cfg(feature = "xz")]
+    #[test]
+    fn test_xz() {
+        use std::io::Write;
+
+        let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 1);
+        encoder.write_all(MAP).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let bytes = decompress(Path::new("app.map.xz"), compressed).unwrap();
+        assert_eq!(bytes, MAP);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_reader_decompresses_incrementally() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(MAP).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut lines = reader(Path::new("app.map.gz"), &compressed[..])
+            .unwrap()
+            .lines();
+        assert_eq!(lines.next().unwrap().unwrap(), "# Path: /target/debug/app");
+
+        let plain = reader(Path::new("app.map"), &b"# P"[..]).unwrap();
+        assert_eq!(plain.lines().next().unwrap().unwrap(), "# P");
+    }
+
+    #[test]
+    fn test_truncated_input_is_an_error() {
+        let result = decompress(Path::new("app.map.gz"), vec![0x1F, 0x8B, 0x08, 0x00]);
+        assert!(result.is_err());
+    }
+}
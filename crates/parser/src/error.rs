@@ -0,0 +1,222 @@
+//! Errors reported by [`crate::parse`].
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use winnow::error::{ContextError, StrContext};
+
+use crate::MapDialect;
+
+/// Why a map file could not be parsed.
+#[derive(Debug)]
+pub enum Error {
+    /// The map file does not exist
+    NotFound(PathBuf),
+    /// The map file exists but cannot be read
+    PermissionDenied(PathBuf),
+    /// The file is binary rather than a text map (e.g. the linked executable);
+    /// `offset` is the first byte that is not valid UTF-8
+    InvalidUtf8 { path: PathBuf, offset: usize },
+    /// The map is compressed with a codec whose cargo feature is disabled
+    UnsupportedCompression {
+        path: PathBuf,
+        compression: &'static str,
+    },
+    /// Any other failure reading (or decompressing) the file
+    Io { path: PathBuf, source: io::Error },
+    /// The map's dialect can only be parsed whole, not streamed
+    Unstreamable { path: PathBuf, dialect: MapDialect },
+    /// The contents are not a map the parser understands
+    Parse(ParseError),
+}
+
+impl Error {
+    /// Classify an I/O failure while reading `path`.
+    pub(crate) fn io(path: &Path, source: io::Error) -> Self {
+        let path = path.to_path_buf();
+        match source.kind() {
+            io::ErrorKind::NotFound => Error::NotFound(path),
+            io::ErrorKind::PermissionDenied => Error::PermissionDenied(path),
+            _ => Error::Io { path, source },
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(path) => write!(f, "{}: no such file", path.display()),
+            Error::PermissionDenied(path) => write!(f, "{}: permission denied", path.display()),
+            Error::InvalidUtf8 { path, offset } => write!(
+                f,
+                "{}: not a text map (invalid UTF-8 at byte {offset})",
+                path.display()
+            ),
+            Error::UnsupportedCompression { path, compression } => write!(
+                f,
+                "{}: {compression}-compressed maps need the `{compression}` feature",
+                path.display()
+            ),
+            Error::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Error::Unstreamable { path, dialect } => write!(
+                f,
+                "{}: {} maps cannot be streamed, only ld64, lld and mold maps can",
+                path.display(),
+                dialect.as_str()
+            ),
+            Error::Parse(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::Parse(error)
+    }
+}
+
+/// Longest excerpt of the offending line kept in a [`ParseError`].
+const SNIPPET_LEN: usize = 120;
+
+/// A map that could not be parsed, located at the offending line.
+///
+/// Rendered like a compiler diagnostic:
+///
+/// ```text
+/// app.map:7:1: invalid Sections
+/// expected `# Sections:`
+///     |
+///   7 | # Sectoins:
+///     | ^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Map file being parsed
+    pub path: PathBuf,
+    /// 1-based line of the failure
+    pub line: usize,
+    /// 1-based column (in characters) of the failure
+    pub column: usize,
+    /// Construct being parsed when the failure occurred (e.g. `Symbols`)
+    pub label: Option<&'static str>,
+    /// What the parser was looking for (e.g. `` `# Sections:` ``)
+    pub expected: Vec<String>,
+    /// Excerpt of the offending line
+    pub snippet: String,
+}
+
+impl ParseError {
+    /// Locate `error`, raised `offset` bytes into `contents`.
+    pub(crate) fn new(path: &Path, contents: &str, offset: usize, error: &ContextError) -> Self {
+        let offset = offset.min(contents.len());
+        let line_start = contents[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = contents[offset..]
+            .find('\n')
+            .map_or(contents.len(), |i| offset + i);
+        let line = &contents[line_start..line_end];
+        let column = contents[line_start..offset].chars().count() + 1;
+
+        let label = error.context().find_map(|context| match context {
+            StrContext::Label(label) => Some(*label),
+            _ => None,
+        });
+        let expected = error
+            .context()
+            .filter_map(|context| match context {
+                StrContext::Expected(value) => Some(value.to_string()),
+                _ => None,
+            })
+            .collect();
+
+        ParseError {
+            path: path.to_path_buf(),
+            line: contents[..line_start].matches('\n').count() + 1,
+            column,
+            label,
+            expected,
+            snippet: line
+                .trim_end_matches('\r')
+                .chars()
+                .take(SNIPPET_LEN)
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}: ", self.path.display(), self.line, self.column)?;
+        match self.label {
+            Some(label) => write!(f, "invalid {label}")?,
+            None => write!(f, "unexpected input")?,
+        }
+        if !self.expected.is_empty() {
+            write!(f, "\nexpected {}", self.expected.join(", "))?;
+        }
+
+        let gutter = self.line.to_string().len();
+        let caret = self.column.min(self.snippet.chars().count() + 1);
+        write!(f, "\n{:gutter$} |", "")?;
+        write!(f, "\n{} | {}", self.line, self.snippet)?;
+        write!(f, "\n{:gutter$} | {:>caret$}", "", "^")
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use winnow::error::StrContextValue;
+
+    #[test]
+    fn test_io_error_kinds() {
+        let path = Path::new("app.map");
+        assert!(matches!(
+            Error::io(path, io::Error::from(io::ErrorKind::NotFound)),
+            Error::NotFound(_)
+        ));
+        assert!(matches!(
+            Error::io(path, io::Error::from(io::ErrorKind::PermissionDenied)),
+            Error::PermissionDenied(_)
+        ));
+        assert!(matches!(
+            Error::io(path, io::Error::from(io::ErrorKind::Interrupted)),
+            Error::Io { .. }
+        ));
+    }
+
+    #[test]
+    fn test_locates_offset() {
+        let contents = "# Path: app\n# Arch: arm64\n# Sectoins:\n";
+        let mut error = ContextError::new();
+        error.push(StrContext::Label("Sections"));
+        error.push(StrContext::Expected(StrContextValue::StringLiteral(
+            "# Sections:",
+        )));
+
+        let offset = contents.find("# Sectoins").unwrap() + 2;
+        let error = ParseError::new(Path::new("app.map"), contents, offset, &error);
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column, 3);
+        assert_eq!(error.label, Some("Sections"));
+        assert_eq!(error.expected, ["`# Sections:`"]);
+        assert_eq!(error.snippet, "# Sectoins:");
+        assert_eq!(
+            error.to_string(),
+            "app.map:3:3: invalid Sections\nexpected `# Sections:`\n  |\n3 | # Sectoins:\n  |   ^"
+        );
+    }
+}
@@ -0,0 +1,614 @@
+//! GNU ld (BFD) map files (`-Map=<file>`).
+//!
+//! The map is a series of free-form blocks introduced by a title line:
+//! archive members pulled in, discarded input sections, the `MEMORY`
+//! configuration and finally the linker script and memory map itself, where
+//! output sections start in column 0, input sections are indented by one space
+//! and symbols are listed by address under the input section that defines them.
+//!
+//! gold writes the same layout under slightly different block titles, so both
+//! linkers share this parser (see [`crate::gold`]).
+
+use std::borrow::Cow;
+
+use winnow::{
+    ascii::{space0, space1, till_line_ending},
+    combinator::{opt, preceded},
+    token::take_till,
+    Parser, Result as ParserResult,
+};
+
+use crate::{
+    detect_binary_format, hex_number, invalid_row, is_non_alloc_section, next_line, InputSection,
+    MapDialect, MapFile, MemoryRegion, ObjectTable, Section, Symbol,
+};
+
+const ARCHIVE_MEMBERS: &str = "Archive member included to satisfy reference by file (symbol)";
+const AS_NEEDED: &str = "As-needed library included to satisfy reference by file (symbol)";
+const COMMON_SYMBOLS: &str = "Allocating common symbols";
+const DISCARDED_SECTIONS: &str = "Discarded input sections";
+const MEMORY_CONFIGURATION: &str = "Memory Configuration";
+const MEMORY_MAP: &str = "Linker script and memory map";
+const MEMORY_REGION_COLUMNS: &str = "Name Origin Length Attributes";
+pub(crate) const GOLD_ARCHIVE_MEMBERS: &str = "Archive member included because of file (symbol)";
+pub(crate) const GOLD_MEMORY_MAP: &str = "Memory map";
+
+const HEADERS: &[&str] = &[
+    ARCHIVE_MEMBERS,
+    AS_NEEDED,
+    COMMON_SYMBOLS,
+    DISCARDED_SECTIONS,
+    MEMORY_CONFIGURATION,
+    MEMORY_MAP,
+    GOLD_ARCHIVE_MEMBERS,
+    GOLD_MEMORY_MAP,
+];
+
+/// Linker script statements that appear where an input section would.
+const SCRIPT_KEYWORDS: &[&str] = &[
+    "ASSERT", "BYTE", "SHORT", "LONG", "QUAD", "SQUAD", "FILL", "KEEP", "PROVIDE", "SORT",
+];
+
+pub(crate) fn is_header(line: &str) -> bool {
+    HEADERS.contains(&line.trim_end())
+}
+
+/// Returns true if the first non-blank line is one of the GNU ld block titles.
+pub(crate) fn sniff(head: &str) -> bool {
+    head.lines()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(is_header)
+}
+
+/// Parse a GNU ld or gold map into the common [`MapFile`] model.
+pub(crate) fn parse<'i>(input: &mut &'i str) -> ParserResult<MapFile<'i>> {
+    let mut objects = ObjectTable::default();
+    let mut memory_regions = Vec::new();
+    let mut memory_map = MemoryMap::default();
+
+    while let Some(line) = next_line(input) {
+        match line.trim_end() {
+            ARCHIVE_MEMBERS | GOLD_ARCHIVE_MEMBERS => archive_members(input, &mut objects),
+            MEMORY_CONFIGURATION => memory_regions = memory_configuration(input)?,
+            MEMORY_MAP | GOLD_MEMORY_MAP => memory_map.parse(input, &mut objects)?,
+            // Common allocations, as-needed libraries and discarded sections
+            // carry nothing the model records; skip to the next block.
+            _ => skip_block(input),
+        }
+    }
+
+    assign_regions(&mut memory_map.sections, &memory_regions);
+    let binary_format = detect_binary_format(&memory_map.sections);
+
+    Ok(MapFile {
+        arch: memory_map.arch,
+        object_files: objects.into_files(),
+        target_path: memory_map.target_path,
+        symbols: memory_map.symbols,
+        dead_stripped: Vec::new(),
+        sections: memory_map.sections,
+        binary_format,
+        memory_regions,
+        dialect: MapDialect::GnuLd,
+        invalid_utf8: 0,
+    })
+}
+
+/// Name the `MEMORY` regions each allocated output section runs from and is loaded into.
+pub(crate) fn assign_regions<'i>(sections: &mut [Section<'i>], regions: &[MemoryRegion<'i>]) {
+    let region_of = |address: u64| {
+        regions.iter().find_map(|region| {
+            (region.origin <= address && address < region.origin.saturating_add(region.length))
+                .then(|| region.name.clone())
+        })
+    };
+    for section in sections.iter_mut() {
+        if section.size == 0 || is_non_alloc_section(&section.section) {
+            continue;
+        }
+        section.execution_region = region_of(section.address);
+        section.load_region = region_of(section.load_address.unwrap_or(section.address));
+    }
+}
+
+/// Return the next line of the current block, stopping before the next title.
+fn block_line<'i>(input: &mut &'i str) -> Option<&'i str> {
+    let mut lookahead = *input;
+    let line = next_line(&mut lookahead)?;
+    if is_header(line) {
+        return None;
+    }
+    *input = lookahead;
+    Some(line)
+}
+
+fn skip_block(input: &mut &str) {
+    while block_line(input).is_some() {}
+}
+
+/// Register archive members (e.g. `libc.a(printf.o)   main.o (printf)`) as object files.
+fn archive_members<'i>(input: &mut &'i str, objects: &mut ObjectTable<'i>) {
+    while let Some(line) = block_line(input) {
+        if line.is_empty() || line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let member = line.split("  ").next().unwrap_or(line).trim();
+        objects.intern(member);
+    }
+}
+
+/// Parse one `MEMORY` region row (e.g. `FLASH  0x08000000  0x00100000  xr`).
+fn memory_region<'i>(input: &mut &'i str) -> ParserResult<MemoryRegion<'i>> {
+    let (name, origin, length, attributes) = (
+        take_till(1.., char::is_whitespace),
+        preceded(space1, hex_number),
+        preceded(space1, hex_number),
+        preceded(space0, till_line_ending),
+    )
+        .parse_next(input)?;
+
+    Ok(MemoryRegion {
+        name: Cow::Borrowed(name),
+        origin,
+        length,
+        attributes: Cow::Borrowed(attributes.trim()),
+    })
+}
+
+/// Parse the region table under `Memory Configuration`, skipping `*default*`.
+fn memory_configuration<'i>(input: &mut &'i str) -> ParserResult<Vec<MemoryRegion<'i>>> {
+    let mut regions = Vec::new();
+    loop {
+        let start = *input;
+        let Some(line) = block_line(input) else {
+            break;
+        };
+        if line.trim().is_empty()
+            || line
+                .split_whitespace()
+                .eq(MEMORY_REGION_COLUMNS.split_whitespace())
+        {
+            continue;
+        }
+        let mut rest = line;
+        let Ok(region) = memory_region(&mut rest) else {
+            return Err(invalid_row(input, start, rest, MEMORY_CONFIGURATION));
+        };
+        if region.name != "*default*" {
+            regions.push(region);
+        }
+    }
+    Ok(regions)
+}
+
+/// Parse the `0xADDR 0xSIZE` pair following a section name.
+fn address_and_size(input: &mut &str) -> ParserResult<(u64, u64)> {
+    (preceded(space1, hex_number), preceded(space1, hex_number)).parse_next(input)
+}
+
+/// Parse the tail of an output section row: `0xADDR 0xSIZE [load address 0xLMA]`.
+fn output_section_tail(input: &mut &str) -> ParserResult<(u64, u64, Option<u64>)> {
+    let (address, size) = address_and_size(input)?;
+    let load_address =
+        opt(preceded((space1, "load address", space1), hex_number)).parse_next(input)?;
+    Ok((address, size, load_address))
+}
+
+/// Parse the tail of an input section row: `0xADDR 0xSIZE file`.
+fn input_section_tail<'i>(input: &mut &'i str) -> ParserResult<(u64, u64, &'i str)> {
+    let (address, size) = address_and_size(input)?;
+    let file = preceded(space0, till_line_ending).parse_next(input)?;
+    Ok((address, size, file.trim_end()))
+}
+
+/// Returns true if `text` starts with an address column (`0x...`), so the row it
+/// belongs to must parse; other rows that do not are script statements.
+fn has_address(text: &str) -> bool {
+    text.trim_start().starts_with("0x")
+}
+
+/// Parse a symbol row (e.g. `                0x0000000000401000                main`).
+fn symbol_row<'i>(input: &mut &'i str) -> ParserResult<(u64, &'i str)> {
+    (
+        preceded(space1, hex_number),
+        preceded(space1, till_line_ending),
+    )
+        .parse_next(input)
+}
+
+/// Symbol rows that are really script assignments (`. = ALIGN (0x8)`, `PROVIDE (...)`).
+fn is_assignment(name: &str) -> bool {
+    name.contains(" = ")
+        || name.starts_with("PROVIDE")
+        || name.starts_with("[!provide]")
+        || name.starts_with("ASSERT")
+}
+
+/// Map a BFD target name from `OUTPUT(...)` to an architecture name.
+fn arch_from_bfd_target(target: &str) -> &str {
+    let bits64 = target.starts_with("elf64") || target.starts_with("pe-x86-64");
+    if target.contains("x86-64") {
+        "x86_64"
+    } else if target.contains("i386") {
+        "i386"
+    } else if target.contains("aarch64") {
+        "aarch64"
+    } else if target.contains("littlearm") || target.contains("bigarm") {
+        "arm"
+    } else if target.contains("riscv") {
+        if bits64 {
+            "riscv64"
+        } else {
+            "riscv32"
+        }
+    } else if target.contains("powerpc") {
+        if bits64 {
+            "powerpc64"
+        } else {
+            "powerpc"
+        }
+    } else {
+        target
+    }
+}
+
+/// State accumulated while walking `Linker script and memory map`.
+struct MemoryMap<'i> {
+    target_path: Cow<'i, str>,
+    arch: Cow<'i, str>,
+    sections: Vec<Section<'i>>,
+    symbols: Vec<Symbol<'i>>,
+    current: Option<InputSection<'i>>,
+    in_non_alloc: bool,
+}
+
+impl Default for MemoryMap<'_> {
+    fn default() -> Self {
+        Self {
+            target_path: Cow::Borrowed(""),
+            arch: Cow::Borrowed("unknown"),
+            sections: Vec::new(),
+            symbols: Vec::new(),
+            current: None,
+            in_non_alloc: false,
+        }
+    }
+}
+
+impl<'i> MemoryMap<'i> {
+    /// Walk the block, failing on the first row with an address that does not parse.
+    fn parse(&mut self, input: &mut &'i str, objects: &mut ObjectTable<'i>) -> ParserResult<()> {
+        loop {
+            let start = *input;
+            let Some(line) = block_line(input) else {
+                break;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let row = if !line.starts_with(char::is_whitespace) {
+                self.top_level(line, input, objects)
+            } else if line.starts_with(' ') && !line[1..].starts_with(char::is_whitespace) {
+                self.input_section(line, input, objects)
+            } else {
+                self.symbol(line)
+            };
+            if let Err(rest) = row {
+                return Err(invalid_row(input, start, rest, MEMORY_MAP));
+            }
+        }
+        self.flush();
+        Ok(())
+    }
+
+    fn flush(&mut self) {
+        if let Some(section) = self.current.take() {
+            section.push_symbols(&mut self.symbols);
+        }
+    }
+
+    /// Column-0 lines: `LOAD`, `OUTPUT(...)`, script commands and output sections.
+    ///
+    /// Fails with the rest of the row if an address follows the name but does not parse.
+    fn top_level(
+        &mut self,
+        line: &'i str,
+        input: &mut &'i str,
+        objects: &mut ObjectTable<'i>,
+    ) -> Result<(), &'i str> {
+        if let Some(path) = line.strip_prefix("LOAD ") {
+            let path = path.trim();
+            if !path.ends_with(".a") {
+                objects.intern(path);
+            }
+            return Ok(());
+        }
+        if let Some(output) = line
+            .strip_prefix("OUTPUT(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            if let Some((path, target)) = output.rsplit_once(' ') {
+                self.target_path = Cow::Borrowed(path);
+                self.arch = Cow::Borrowed(arch_from_bfd_target(target));
+            }
+            return Ok(());
+        }
+
+        self.flush();
+        let mut rest = line;
+        let Ok(name) = take_till::<_, _, ()>(1.., char::is_whitespace).parse_next(&mut rest) else {
+            return Ok(());
+        };
+        let columns = rest;
+        let tail = output_section_tail(&mut rest);
+        if tail.is_err() && has_address(columns) {
+            return Err(rest);
+        }
+        let tail = tail.ok().or_else(|| {
+            // Long names push the address and size onto the following line.
+            let mut lookahead = *input;
+            let mut next = next_line(&mut lookahead)?;
+            let tail = output_section_tail(&mut next).ok()?;
+            *input = lookahead;
+            Some(tail)
+        });
+        // Lines without addresses are script commands or empty output sections.
+        let Some((address, size, load_address)) = tail else {
+            self.in_non_alloc = true;
+            return Ok(());
+        };
+
+        self.in_non_alloc = is_non_alloc_section(name);
+        self.sections.push(Section {
+            address,
+            size,
+            segment: Cow::Borrowed(name),
+            section: Cow::Borrowed(name),
+            load_address,
+            alignment: None,
+            load_region: None,
+            execution_region: None,
+        });
+        Ok(())
+    }
+
+    /// One-space indented lines: input sections, `*fill*`, gold's `** fill` and script patterns.
+    ///
+    /// Fails with the rest of the row if an address follows the name but does not parse.
+    fn input_section(
+        &mut self,
+        line: &'i str,
+        input: &mut &'i str,
+        objects: &mut ObjectTable<'i>,
+    ) -> Result<(), &'i str> {
+        let mut rest = &line[1..];
+        let Ok(name) = take_till::<_, _, ()>(1.., char::is_whitespace).parse_next(&mut rest) else {
+            return Ok(());
+        };
+        if name.starts_with('*')
+            || SCRIPT_KEYWORDS
+                .iter()
+                .any(|keyword| name.starts_with(keyword))
+        {
+            return Ok(());
+        }
+
+        let columns = rest;
+        let tail = input_section_tail(&mut rest);
+        if tail.is_err() && has_address(columns) {
+            return Err(rest);
+        }
+        let tail = tail.ok().or_else(|| {
+            // Long input section names wrap: the address row follows on its own line.
+            let mut lookahead = *input;
+            let mut next = next_line(&mut lookahead)?;
+            let tail = input_section_tail(&mut next).ok()?;
+            *input = lookahead;
+            Some(tail)
+        });
+        let Some((address, size, file)) = tail else {
+            return Ok(());
+        };
+
+        self.flush();
+        if self.in_non_alloc || file.is_empty() {
+            return Ok(());
+        }
+        self.current = Some(InputSection {
+            name,
+            address,
+            size,
+            file_index: objects.intern(file),
+            symbols: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Deeply indented `0xADDR name` rows belonging to the current input section.
+    ///
+    /// Fails with the rest of the row if it starts with an address but does not parse.
+    fn symbol(&mut self, line: &'i str) -> Result<(), &'i str> {
+        let mut rest = line;
+        let Ok((address, name)) = symbol_row(&mut rest) else {
+            // Rows without an address are script statements (`[!provide] PROVIDE (...)`).
+            return if has_address(line) { Err(rest) } else { Ok(()) };
+        };
+        let name = name.trim_end();
+        // `(size before relaxing)` annotations follow the row they describe.
+        if name.starts_with("0x") || name.starts_with('(') || is_assignment(name) {
+            return Ok(());
+        }
+        if let Some(current) = self.current.as_mut() {
+            current.symbols.push((address, None, name));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        assert!(sniff(
+            "\nArchive member included to satisfy reference by file (symbol)\n"
+        ));
+        assert!(sniff("Memory Configuration\n\nName Origin Length\n"));
+        assert!(!sniff("# Path: /target/debug/app\n"));
+    }
+
+    #[test]
+    fn test_memory_region() {
+        let mut input = "FLASH            0x0000000008000000 0x0000000000100000 xr";
+        let region = memory_region(&mut input).unwrap();
+        assert_eq!(region.name, "FLASH");
+        assert_eq!(region.origin, 0x0000000008000000);
+        assert_eq!(region.length, 0x0000000000100000);
+        assert_eq!(region.attributes, "xr");
+    }
+
+    #[test]
+    fn test_memory_configuration_skips_default() {
+        let mut input = "
+Name             Origin             Length             Attributes
+RAM              0x0000000020000000 0x0000000000020000 xrw
+*default*        0x0000000000000000 0xffffffffffffffff
+
+Linker script and memory map
+";
+        let regions = memory_configuration(&mut input).unwrap();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].name, "RAM");
+        assert!(input.starts_with(MEMORY_MAP));
+    }
+
+    #[test]
+    fn test_archive_members() {
+        let mut input = "
+/usr/lib/libc.a(printf.o)     main.o (printf)
+/usr/lib/libc.a(vfprintf.o)
+                              /usr/lib/libc.a(printf.o) (vfprintf)
+";
+        let mut objects = ObjectTable::default();
+        archive_members(&mut input, &mut objects);
+        let files = objects.into_files();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "/usr/lib/libc.a(printf.o)");
+        assert_eq!(files[1].path, "/usr/lib/libc.a(vfprintf.o)");
+    }
+
+    #[test]
+    fn test_input_section_sizes_symbols_by_next_address() {
+        let mut input = "
+.text           0x0000000000401000       0x60
+ *(.text)
+ .text          0x0000000000401000       0x40 main.o
+                0x0000000000401000                main
+                0x0000000000401030                helper
+ .text._ZN3foo3barE
+                0x0000000000401040       0x20 libfoo.a(foo.o)
+";
+        let mut objects = ObjectTable::default();
+        let mut map = MemoryMap::default();
+        map.parse(&mut input, &mut objects).unwrap();
+
+        assert_eq!(map.sections.len(), 1);
+        assert_eq!(map.sections[0].segment, ".text");
+        assert_eq!(map.sections[0].size, 0x60);
+        assert_eq!(map.symbols.len(), 3);
+        assert_eq!(map.symbols[0].name, "main");
+        assert_eq!(map.symbols[0].size, 0x30);
+        assert_eq!(map.symbols[1].name, "helper");
+        assert_eq!(map.symbols[1].size, 0x10);
+        assert_eq!(map.symbols[2].name, ".text._ZN3foo3barE");
+        assert_eq!(map.symbols[2].address, 0x401040);
+        assert_eq!(map.symbols[2].file_index, 1);
+    }
+
+    #[test]
+    fn test_skips_assignments_and_debug_sections() {
+        let mut input = "
+.bss            0x0000000000602010        0x8
+                0x0000000000602010                . = ALIGN (0x8)
+ .bss           0x0000000000602010        0x8 main.o
+                0x0000000000602010                __bss_start = .
+                0x0000000000602010                counter
+.debug_info     0x0000000000000000       0x39
+ .debug_info    0x0000000000000000       0x39 main.o
+OUTPUT(a.out elf64-x86-64)
+";
+        let mut objects = ObjectTable::default();
+        let mut map = MemoryMap::default();
+        map.parse(&mut input, &mut objects).unwrap();
+
+        assert_eq!(map.sections.len(), 2);
+        assert_eq!(map.symbols.len(), 1);
+        assert_eq!(map.symbols[0].name, "counter");
+        assert_eq!(map.target_path, "a.out");
+        assert_eq!(map.arch, "x86_64");
+    }
+
+    #[test]
+    fn test_output_section_load_address() {
+        let mut input =
+            ".data           0x0000000020000000       0x10 load address 0x0000000008001000";
+        let mut objects = ObjectTable::default();
+        let mut map = MemoryMap::default();
+        map.parse(&mut input, &mut objects).unwrap();
+        assert_eq!(map.sections.len(), 1);
+        assert_eq!(map.sections[0].load_address, Some(0x0000000008001000));
+    }
+
+    #[test]
+    fn test_arch_from_bfd_target() {
+        assert_eq!(arch_from_bfd_target("elf64-x86-64"), "x86_64");
+        assert_eq!(arch_from_bfd_target("elf32-littlearm"), "arm");
+        assert_eq!(arch_from_bfd_target("elf64-littleriscv"), "riscv64");
+        assert_eq!(arch_from_bfd_target("elf32-avr"), "elf32-avr");
+    }
+
+    #[test]
+    fn test_rows_with_bad_addresses_are_errors() {
+        let map = "Memory Configuration
+
+Name             Origin             Length             Attributes
+FLASH            0x0000000008000000 0x0000000000100000 xr
+
+Linker script and memory map
+
+.text           0x0000000000401000       0x60
+                [!provide]                PROVIDE (__text_start = .)
+ .text          0x0000000000401000       0x40 main.o
+                0x0000000000401000                main
+";
+        assert_eq!(
+            crate::parse_str_as(map, MapDialect::GnuLd)
+                .unwrap()
+                .symbols
+                .len(),
+            1
+        );
+
+        for (truncated, line, label) in [
+            ("FLASH            0x00000000080", 4, MEMORY_CONFIGURATION),
+            (".text           0x0000000000401000       0x", 8, MEMORY_MAP),
+            (" .text          0x0000000000401000", 10, MEMORY_MAP),
+            ("                0x0000000000401000", 11, MEMORY_MAP),
+        ] {
+            let start = map.find(truncated).unwrap();
+            let end = start + map[start..].find('\n').unwrap();
+            let contents = format!("{}{}", &map[..start + truncated.len()], &map[end..]);
+            let Err(crate::Error::Parse(error)) = crate::parse_str_as(&contents, MapDialect::GnuLd)
+            else {
+                panic!("expected a parse error for {truncated:?}");
+            };
+            assert_eq!(
+                (error.line, error.label),
+                (line, Some(label)),
+                "{truncated:?}"
+            );
+            assert_eq!(error.snippet, truncated);
+        }
+    }
+}
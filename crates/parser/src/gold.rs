@@ -0,0 +1,49 @@
+//! GNU gold map files (`--Map=<file>`).
+//!
+//! gold prints the same output section / input section / symbol layout as
+//! GNU ld, under its own block titles (`Memory map` instead of `Linker script
+//! and memory map`) and without the script patterns, so parsing is shared with
+//! [`crate::gnu`].
+
+use winnow::Result as ParserResult;
+
+use crate::{gnu, MapDialect, MapFile};
+
+/// Returns true if the map carries gold's block titles.
+pub(crate) fn sniff(head: &str) -> bool {
+    let mut titles = head
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| gnu::is_header(line));
+    match titles.next() {
+        Some(gnu::GOLD_ARCHIVE_MEMBERS) | Some(gnu::GOLD_MEMORY_MAP) => true,
+        Some(_) => titles.any(|title| title == gnu::GOLD_MEMORY_MAP),
+        None => false,
+    }
+}
+
+/// Parse a gold map into the common [`MapFile`] model.
+pub(crate) fn parse<'i>(input: &mut &'i str) -> ParserResult<MapFile<'i>> {
+    let mut map = gnu::parse(input)?;
+    map.dialect = MapDialect::Gold;
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        assert!(sniff(
+            "Archive member included because of file (symbol)\n\nlibc.a(printf.o)\n"
+        ));
+        assert!(sniff(
+            "Discarded input sections\n\n .text 0x0 0x0 main.o\n\nMemory map\n"
+        ));
+        assert!(!sniff(
+            "Discarded input sections\n\nLinker script and memory map\n"
+        ));
+        assert!(!sniff("# Path: /target/debug/app\n"));
+    }
+}
@@ -0,0 +1,318 @@
+//! IAR ILINK map files (`--map`).
+//!
+//! The `PLACEMENT SUMMARY` lists every input section under the placement
+//! directive (`"P1":`, `"P2", part 1 of 2:`) that put it in memory, and the
+//! `ENTRY LIST` gives symbol addresses and sizes:
+//!
+//! ```text
+//! "P1":                                       0x64
+//!   .text              ro code         0x40    0x28  main.o [1]
+//!
+//! Entry                       Address   Size  Type      Object
+//! main                            0x41   0x28  Code  Gb  main.o [1]
+//! ```
+//!
+//! Objects carry a `[N]` footnote naming the directory or library they came
+//! from; these are resolved once the whole map has been read. Addresses may use
+//! `'` digit separators (`0x2000'0000`).
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use winnow::stream::Offset;
+use winnow::Result as ParserResult;
+
+use crate::{
+    attach_symbols, next_line, parse_hex, BinaryFormat, InputSection, MapDialect, MapFile,
+    ObjectTable, Section,
+};
+
+const BANNER: &str = "IAR ELF Linker";
+const PLACEMENT_SUMMARY: &str = "*** PLACEMENT SUMMARY";
+
+/// Words of the `Kind` column (e.g. `ro code`, `inited`, `uninit`).
+const KINDS: &[&str] = &[
+    "ro", "rw", "code", "data", "const", "inited", "zero", "uninit", "noinit",
+];
+
+/// Returns true if the head carries the ILINK banner or its placement summary title.
+pub(crate) fn sniff(head: &str) -> bool {
+    head.lines().any(|line| {
+        line.trim_start_matches('#')
+            .trim_start()
+            .starts_with(BANNER)
+            || line.trim() == PLACEMENT_SUMMARY
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    Other,
+    Placement,
+    Entries,
+}
+
+/// Parse an address or size, ignoring `'` digit separators (e.g. `0x2000'0000`).
+fn number(token: &str) -> Option<u64> {
+    parse_hex(&token.strip_prefix("0x")?.replace('\'', ""))
+}
+
+/// Placement name of a placement title (e.g. `"P2", part 1 of 2:   0x8` -> `P2`).
+fn placement(line: &str) -> Option<&str> {
+    let (name, _) = line.strip_prefix('"')?.split_once('"')?;
+    Some(name)
+}
+
+/// Parse a placed input section (e.g. `.text  ro code  0x40  0x28  main.o [1]`).
+///
+/// Container rows (`<Block>`, `<Init block>`) have no kind and are skipped, as
+/// are the `- 0x..` rows closing each placement.
+fn placement_row(line: &str) -> Option<(&str, u64, u64, &str)> {
+    let text = line.trim();
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let position = tokens.iter().position(|token| token.starts_with("0x"))?;
+    let address = number(tokens[position])?;
+    let size = number(tokens.get(position + 1)?)?;
+    let kinds = tokens[..position]
+        .iter()
+        .rev()
+        .take_while(|token| KINDS.contains(token))
+        .count();
+    if kinds == 0 || kinds == position {
+        return None;
+    }
+    let name_end = tokens[position - kinds - 1];
+    let name = &text[..name_end.offset_from(&text) + name_end.len()];
+    let object = tokens
+        .get(position + 2)
+        .map_or("", |first| &text[first.offset_from(&text)..]);
+    Some((name, address, size, object))
+}
+
+/// Parse an entry (e.g. `main  0x41  0x28  Code  Gb  main.o [1]`), whose size is optional.
+///
+/// Linker-created entries (`--` type) are skipped and the Thumb bit is
+/// cleared from code addresses.
+fn entry_row(line: &str) -> Option<(u64, Option<u64>, &str)> {
+    let mut tokens = line.split_whitespace();
+    let name = tokens.next()?;
+    let mut address = number(tokens.next()?)?;
+    let mut kind = tokens.next()?;
+    let size = number(kind);
+    if size.is_some() {
+        kind = tokens.next()?;
+    }
+    match kind {
+        "Code" => address &= !1,
+        "Data" => {}
+        _ => return None,
+    }
+    Some((address, size.filter(|&size| size > 0), name))
+}
+
+/// Parse a footnote (`[1] = C:\proj\Debug\Obj`) or module summary title (`dl7M_tln.a: [2]`).
+fn footnote(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if let Some((index, path)) = line.strip_prefix('[').and_then(|l| l.split_once("] = ")) {
+        return Some((index, path.trim()));
+    }
+    let (path, index) = line.rsplit_once(": [")?;
+    Some((index.strip_suffix(']')?, path))
+}
+
+/// Resolve an object reference (`main.o [1]`) against the footnotes.
+fn resolve_object<'i>(object: &'i str, footnotes: &HashMap<&str, &str>) -> Cow<'i, str> {
+    let Some((name, index)) = object.rsplit_once(" [") else {
+        return Cow::Borrowed(object);
+    };
+    let Some(path) = index.strip_suffix(']').and_then(|i| footnotes.get(i)) else {
+        return Cow::Borrowed(name);
+    };
+    if path.ends_with(".a") {
+        Cow::Owned(format!("{}({})", path, name))
+    } else {
+        let separator = if path.contains('\\') { '\\' } else { '/' };
+        Cow::Owned(format!("{}{}{}", path, separator, name))
+    }
+}
+
+/// Parse an ILINK map into the common [`MapFile`] model.
+pub(crate) fn parse<'i>(input: &mut &'i str) -> ParserResult<MapFile<'i>> {
+    let mut objects = ObjectTable::default();
+    let mut sections = Vec::new();
+    let mut input_sections = Vec::new();
+    let mut symbols = Vec::new();
+    let mut footnotes = HashMap::new();
+    let mut target_path = "";
+    let mut arch = Cow::Borrowed("unknown");
+    let mut block = Block::Other;
+    let mut region = None;
+    let mut wrapped_name: Option<&str> = None;
+
+    while let Some(line) = next_line(input) {
+        let trimmed = line.trim();
+        if let Some(title) = trimmed.strip_prefix("*** ") {
+            block = match title {
+                "PLACEMENT SUMMARY" => Block::Placement,
+                "ENTRY LIST" => Block::Entries,
+                _ => Block::Other,
+            };
+            continue;
+        }
+        if trimmed.starts_with('*') {
+            continue;
+        }
+        if let Some(header) = trimmed.strip_prefix('#') {
+            let header = header.trim();
+            if let Some(path) = header.strip_prefix("Output file") {
+                target_path = path.trim_start().trim_start_matches('=').trim();
+            } else if let Some((_, target)) = header
+                .strip_prefix(BANNER)
+                .and_then(|banner| banner.split_once(" for "))
+            {
+                let target = target.split("  ").next().unwrap_or_default();
+                arch = Cow::Owned(target.trim().to_lowercase());
+            }
+            continue;
+        }
+        if let Some((index, path)) = footnote(line) {
+            footnotes.insert(index, path);
+            continue;
+        }
+
+        match block {
+            Block::Placement => {
+                if trimmed.starts_with("Unused ranges") {
+                    block = Block::Other;
+                } else if let Some(name) = placement(trimmed) {
+                    region = Some(name);
+                } else if let Some((name, address, size, object)) = placement_row(line) {
+                    sections.push(Section {
+                        address,
+                        size,
+                        segment: Cow::Borrowed(region.unwrap_or_default()),
+                        section: Cow::Borrowed(name),
+                        execution_region: region.map(Cow::Borrowed),
+                        ..Default::default()
+                    });
+                    input_sections.push(InputSection {
+                        name,
+                        address,
+                        size,
+                        file_index: objects.intern(object),
+                        symbols: Vec::new(),
+                    });
+                }
+            }
+            Block::Entries => {
+                // Long names are printed on a line of their own.
+                if !trimmed.is_empty() && !trimmed.contains(char::is_whitespace) {
+                    wrapped_name = Some(trimmed);
+                    continue;
+                }
+                let entry = match wrapped_name.take() {
+                    Some(name) => entry_row(&format!("{} {}", name, trimmed))
+                        .map(|(address, size, _)| (address, size, name)),
+                    None => entry_row(line),
+                };
+                symbols.extend(entry);
+            }
+            Block::Other => {}
+        }
+    }
+
+    attach_symbols(&mut input_sections, symbols);
+    let mut map_symbols = Vec::new();
+    for section in input_sections {
+        section.push_symbols(&mut map_symbols);
+    }
+
+    let mut object_files = objects.into_files();
+    for object in &mut object_files {
+        if let Cow::Borrowed(path) = object.path {
+            object.path = resolve_object(path, &footnotes);
+        }
+    }
+
+    Ok(MapFile {
+        arch,
+        object_files,
+        target_path: Cow::Borrowed(target_path),
+        symbols: map_symbols,
+        dead_stripped: Vec::new(),
+        sections,
+        binary_format: BinaryFormat::Elf,
+        memory_regions: Vec::new(),
+        dialect: MapDialect::Iar,
+        invalid_utf8: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        assert!(sniff(
+            "####\n#\n# IAR ELF Linker V8.50.9.278/W32 for ARM          17/Oct/2026  10:00:00\n"
+        ));
+        assert!(!sniff("Linker script and memory map\n"));
+    }
+
+    #[test]
+    fn test_placement_row() {
+        assert_eq!(
+            placement_row("  .text              ro code         0x40    0x28  main.o [1]"),
+            Some((".text", 0x40, 0x28, "main.o [1]"))
+        );
+        assert_eq!(
+            placement_row("  Initializer bytes  const           0xb4     0x8  <for P2-1>"),
+            Some(("Initializer bytes", 0xb4, 0x8, "<for P2-1>"))
+        );
+        assert_eq!(
+            placement_row("    .bss             zero     0x2000'0004     0x4  main.o [1]"),
+            Some((".bss", 0x2000_0004, 0x4, "main.o [1]"))
+        );
+        assert_eq!(
+            placement_row("  P2-1                        0x2000'0000     0x8  <Init block>"),
+            None
+        );
+        assert_eq!(
+            placement_row("                            - 0x2000'0008     0x8"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_entry_row() {
+        assert_eq!(
+            entry_row("main                            0x41   0x28  Code  Gb  main.o [1]"),
+            Some((0x40, Some(0x28), "main"))
+        );
+        assert_eq!(
+            entry_row(
+                "__vector_table                   0x0          Data  Gb  vector_table_M.o [4]"
+            ),
+            Some((0x0, None, "__vector_table"))
+        );
+        assert_eq!(
+            entry_row(".iar.init_table$$Base          0x1cc          --   Gb  - Linker created -"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_object() {
+        let footnotes = HashMap::from([("1", "C:\\proj\\Debug\\Obj"), ("2", "dl7M_tln.a")]);
+        assert_eq!(
+            resolve_object("main.o [1]", &footnotes),
+            "C:\\proj\\Debug\\Obj\\main.o"
+        );
+        assert_eq!(
+            resolve_object("exit.o [2]", &footnotes),
+            "dl7M_tln.a(exit.o)"
+        );
+        assert_eq!(resolve_object("<for P2-1>", &footnotes), "<for P2-1>");
+    }
+}
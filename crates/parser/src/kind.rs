@@ -0,0 +1,243 @@
+//! Classification of symbols into [`SymbolKind`]s.
+//!
+//! Linkers list compiler- and linker-generated entries next to ordinary symbols:
+//! ld64 prints `literal string: …`, `FDE for: …`, `CIE` and `anon` rows, and ELF
+//! maps attribute bytes to `<internal>` inputs. Names are matched first, then the
+//! object the symbol comes from, and finally the section it lands in.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::{MapFile, ObjectFile, ObjectKind, Section, Symbol};
+
+/// What a symbol is, derived from its name, object file and section.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SymbolKind {
+    /// Code (e.g. `__TEXT/__text`, `.text`)
+    Function,
+    /// Any other named data
+    Data,
+    /// String literal the linker names by its contents (ld64 `literal string: …`)
+    LiteralString,
+    /// Compiler-named C string (e.g. `__cstring`, `.rodata.str1.1`, MSVC `??_C@…`)
+    LiteralCString,
+    /// Unwind frame description entry (ld64 `FDE for: …`, `.eh_frame`)
+    Fde,
+    /// Unwind common information entry (ld64 `CIE`)
+    Cie,
+    /// Language-specific exception table (`GCC_except_table…`, `.gcc_except_table`)
+    ExceptionTable,
+    /// Unnamed constant or local (e.g. `anon`, `l_anon.…`, `ltmp0`)
+    Anonymous,
+    /// Call stub into a dylib or PLT entry (e.g. `_free.stub`, `__stubs`, `.plt`)
+    Stub,
+    /// Produced by the linker itself rather than any input object
+    LinkerSynthesized,
+}
+
+impl SymbolKind {
+    /// Every kind, in declaration order.
+    pub const ALL: [SymbolKind; 10] = [
+        SymbolKind::Function,
+        SymbolKind::Data,
+        SymbolKind::LiteralString,
+        SymbolKind::LiteralCString,
+        SymbolKind::Fde,
+        SymbolKind::Cie,
+        SymbolKind::ExceptionTable,
+        SymbolKind::Anonymous,
+        SymbolKind::Stub,
+        SymbolKind::LinkerSynthesized,
+    ];
+
+    /// Returns the human-readable name of the kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Data => "data",
+            SymbolKind::LiteralString => "literal string",
+            SymbolKind::LiteralCString => "C string",
+            SymbolKind::Fde => "FDE",
+            SymbolKind::Cie => "CIE",
+            SymbolKind::ExceptionTable => "exception table",
+            SymbolKind::Anonymous => "anonymous",
+            SymbolKind::Stub => "stub",
+            SymbolKind::LinkerSynthesized => "linker synthesized",
+        }
+    }
+
+    /// The kind implied by a symbol's name alone, if any.
+    fn from_name(name: &str) -> Option<Self> {
+        if name.starts_with("literal string: ") {
+            Some(SymbolKind::LiteralString)
+        } else if name.starts_with(".rodata.str") || name.starts_with("??_C@") {
+            Some(SymbolKind::LiteralCString)
+        } else if name.starts_with("FDE for: ") {
+            Some(SymbolKind::Fde)
+        } else if name == "CIE" {
+            Some(SymbolKind::Cie)
+        } else if name.starts_with("GCC_except_table") {
+            Some(SymbolKind::ExceptionTable)
+        } else if is_anonymous(name) {
+            Some(SymbolKind::Anonymous)
+        } else if name.ends_with(".stub") || name.ends_with("@plt") {
+            Some(SymbolKind::Stub)
+        } else {
+            None
+        }
+    }
+
+    /// The kind of anything else placed in output section `segment`/`section`.
+    fn from_section(segment: &str, section: &str) -> Self {
+        match section {
+            "__stubs" | "__auth_stubs" | "__stub_helper" | ".plt" | ".plt.sec" | ".iplt" => {
+                SymbolKind::Stub
+            }
+            "__eh_frame" | ".eh_frame" => SymbolKind::Fde,
+            "__gcc_except_tab" | ".gcc_except_table" => SymbolKind::ExceptionTable,
+            "__cstring" => SymbolKind::LiteralCString,
+            "__text" if segment == "__TEXT" => SymbolKind::Function,
+            _ if section.starts_with(".rodata.str") => SymbolKind::LiteralCString,
+            _ if [".text", ".init", ".fini", "i."]
+                .iter()
+                .any(|prefix| section.starts_with(prefix)) =>
+            {
+                SymbolKind::Function
+            }
+            _ => SymbolKind::Data,
+        }
+    }
+}
+
+/// Returns true for compiler-generated names of unnamed values.
+fn is_anonymous(name: &str) -> bool {
+    name == "anon"
+        || ["l_anon.", "anon.", ".Lanon.", "l___unnamed_"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+        || name
+            .strip_prefix("ltmp")
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Assigns [`SymbolKind`]s using the sections and object files of a map.
+#[derive(Default)]
+pub(crate) struct Classifier {
+    /// Output sections as `(start, end, kind)`, sorted by start
+    ranges: Vec<(u64, u64, SymbolKind)>,
+    /// Indices of the linker's own pseudo object files
+    synthesized: HashSet<u32>,
+}
+
+impl Classifier {
+    pub(crate) fn new(map: &MapFile) -> Self {
+        let mut classifier = Classifier::default();
+        for section in &map.sections {
+            classifier.push_section(section);
+        }
+        for object in &map.object_files {
+            classifier.push_object(object);
+        }
+        classifier
+    }
+
+    /// Classify symbols placed in `section` too.
+    pub(crate) fn push_section(&mut self, section: &Section) {
+        if section.size == 0 {
+            return;
+        }
+        let start = section.address;
+        let position = self.ranges.partition_point(|&(other, _, _)| other <= start);
+        self.ranges.insert(
+            position,
+            (
+                start,
+                start.saturating_add(section.size),
+                SymbolKind::from_section(&section.segment, &section.section),
+            ),
+        );
+    }
+
+    /// Classify symbols of `object` too.
+    pub(crate) fn push_object(&mut self, object: &ObjectFile) {
+        if object.kind == ObjectKind::LinkerSynthesized {
+            self.synthesized.insert(object.index);
+        }
+    }
+
+    pub(crate) fn classify(&self, symbol: &Symbol) -> SymbolKind {
+        if let Some(kind) = SymbolKind::from_name(&symbol.name) {
+            return kind;
+        }
+        if self.synthesized.contains(&symbol.file_index) {
+            return SymbolKind::LinkerSynthesized;
+        }
+        let position = self
+            .ranges
+            .partition_point(|&(start, _, _)| start <= symbol.address);
+        position
+            .checked_sub(1)
+            .map(|p| self.ranges[p])
+            .filter(|&(_, end, _)| symbol.address < end)
+            .map_or(SymbolKind::Data, |(_, _, kind)| kind)
+    }
+}
+
+/// Assign the kind of every symbol in `map`.
+pub(crate) fn classify_symbols(map: &mut MapFile) {
+    let classifier = Classifier::new(map);
+    for symbol in &mut map.symbols {
+        symbol.kind = classifier.classify(symbol);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        let cases = [
+            ("literal string: hello", Some(SymbolKind::LiteralString)),
+            ("FDE for: _main", Some(SymbolKind::Fde)),
+            ("CIE", Some(SymbolKind::Cie)),
+            ("GCC_except_table12", Some(SymbolKind::ExceptionTable)),
+            ("anon", Some(SymbolKind::Anonymous)),
+            ("l_anon.5c3a.12", Some(SymbolKind::Anonymous)),
+            ("ltmp3", Some(SymbolKind::Anonymous)),
+            ("ltmpfoo", None),
+            ("___error.stub", Some(SymbolKind::Stub)),
+            ("??_C@_05PDJBBECF@hello@", Some(SymbolKind::LiteralCString)),
+            ("__ZN3std2rt10lang_start17h0123456789abcdefE.llvm.123", None),
+        ];
+        for (name, kind) in cases {
+            assert_eq!(SymbolKind::from_name(name), kind, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_from_section() {
+        assert_eq!(
+            SymbolKind::from_section("__TEXT", "__text"),
+            SymbolKind::Function
+        );
+        assert_eq!(
+            SymbolKind::from_section("__TEXT", "__const"),
+            SymbolKind::Data
+        );
+        assert_eq!(
+            SymbolKind::from_section("__TEXT", "__stubs"),
+            SymbolKind::Stub
+        );
+        assert_eq!(
+            SymbolKind::from_section(".text", ".text$mn"),
+            SymbolKind::Function
+        );
+        assert_eq!(
+            SymbolKind::from_section(".rodata", ".rodata.str1.1"),
+            SymbolKind::LiteralCString
+        );
+        assert_eq!(SymbolKind::from_section(".data", ".data"), SymbolKind::Data);
+    }
+}
@@ -0,0 +1,547 @@
+//! Apple ld64 / ld-prime map files (`-map` output).
+//!
+//! The map is a sequence of `# `-prefixed headers: `# Path:`, `# Arch:`,
+//! `# Object files:`, `# Sections:` and `# Symbols:`, optionally followed by
+//! `# Dead Stripped Symbols:` when linking with `-dead_strip`.
+
+use std::borrow::Cow;
+
+use winnow::{
+    ascii::{digit1, line_ending, multispace0, till_line_ending},
+    combinator::{alt, delimited, eof, opt, peek, preceded, repeat, repeat_till, terminated},
+    error::{ContextError, StrContext, StrContextValue},
+    token::literal,
+    Parser, Result as ParserResult,
+};
+
+use crate::{
+    detect_binary_format, hex_number, spaces, DeadSymbol, MapDialect, MapFile, ObjectFile, Section,
+    Symbol, SymbolKind,
+};
+
+#[derive(Debug)]
+pub(crate) enum MapFileHeaders {
+    Path,
+    Architecture,
+    ObjectFiles,
+    Symbols,
+    Sections,
+    DeadStrippedSymbols,
+}
+
+impl MapFileHeaders {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            MapFileHeaders::Path => "# Path:",
+            MapFileHeaders::Architecture => "# Arch:",
+            MapFileHeaders::ObjectFiles => "# Object files:",
+            MapFileHeaders::Sections => "# Sections:",
+            MapFileHeaders::Symbols => "# Symbols:",
+            MapFileHeaders::DeadStrippedSymbols => "# Dead Stripped Symbols:",
+        }
+    }
+}
+
+/// Match a header literal, reporting it as the expected construct on failure.
+fn header<'i>(header: MapFileHeaders) -> impl Parser<&'i str, &'i str, ContextError> {
+    literal(header.as_str()).context(StrContext::Expected(StrContextValue::StringLiteral(
+        header.as_str(),
+    )))
+}
+
+/// Parse architecture header (e.g. `# Arch: arm64` -> `arm64`).
+fn arch<'i>(input: &mut &'i str) -> ParserResult<&'i str> {
+    preceded(
+        preceded(
+            opt(line_ending),
+            preceded(header(MapFileHeaders::Architecture), multispace0),
+        ),
+        till_line_ending,
+    )
+    .parse_next(input)
+}
+
+/// Parse one object file line (e.g. `[ 66] /path/libunwind.tbd`).
+fn object_file<'i>(input: &mut &'i str) -> ParserResult<ObjectFile<'i>> {
+    let (index, path) = (file_index, preceded(multispace0, till_line_ending)).parse_next(input)?;
+
+    Ok(ObjectFile::new(index, path))
+}
+
+/// Parse a bracketed object file index (e.g. `[ 66]` -> `66`).
+fn file_index(input: &mut &str) -> ParserResult<u32> {
+    delimited('[', preceded(multispace0, digit1.parse_to()), ']').parse_next(input)
+}
+
+/// Parse entire object files block after `# Object files:` until next header.
+fn object_files<'i>(input: &mut &'i str) -> ParserResult<Vec<ObjectFile<'i>>> {
+    delimited(
+        opt(line_ending),
+        header(MapFileHeaders::ObjectFiles),
+        line_ending,
+    )
+    .parse_next(input)?;
+
+    repeat(
+        0..,
+        terminated(preceded(peek('['), object_file), opt(line_ending)),
+    )
+    .parse_next(input)
+}
+
+/// Parse one symbol row (e.g. `0xADDR\t0xSIZE\t[  1] name`).
+pub(crate) fn symbol<'i>(input: &mut &'i str) -> ParserResult<Symbol<'i>> {
+    let address = preceded(multispace0, hex_number).parse_next(input)?;
+    let size = preceded(multispace0, hex_number).parse_next(input)?;
+    let file_index = preceded(preceded(opt(line_ending), spaces), file_index).parse_next(input)?;
+    let name = preceded(spaces, till_line_ending).parse_next(input)?;
+
+    Ok(Symbol {
+        address,
+        size,
+        file_index,
+        kind: SymbolKind::Data,
+        name: Cow::Borrowed(name),
+    })
+}
+
+/// Parse consecutive symbol rows until header or EOF.
+fn symbols<'i>(input: &mut &'i str) -> ParserResult<Vec<Symbol<'i>>> {
+    repeat_till(
+        0..,
+        terminated(symbol, opt(line_ending)),
+        preceded(multispace0, alt((peek(literal("# ")), eof))),
+    )
+    .parse_next(input)
+    .map(|(symbols, _)| symbols)
+}
+
+/// Parse full symbol table after `# Symbols:`.
+fn symbol_table<'i>(input: &mut &'i str) -> ParserResult<Vec<Symbol<'i>>> {
+    terminated(header(MapFileHeaders::Symbols), line_ending).parse_next(input)?;
+    terminated(till_line_ending, line_ending).parse_next(input)?;
+    #[cfg(feature = "parallel")]
+    if let Some(symbols) = par_symbols(input) {
+        return Ok(symbols);
+    }
+    symbols.parse_next(input)
+}
+
+/// Tables shorter than this (in bytes) are parsed on the calling thread.
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_LEN: usize = 1 << 20;
+
+/// Approximate length of the line-aligned chunks handed to the thread pool.
+#[cfg(feature = "parallel")]
+const CHUNK_LEN: usize = 256 * 1024;
+
+/// Parse a large symbol table in line-aligned chunks on the rayon thread pool.
+///
+/// Returns `None`, leaving `input` untouched, for small tables or if any chunk
+/// fails to parse; the sequential parser then runs and reports the error at
+/// its exact position.
+#[cfg(feature = "parallel")]
+fn par_symbols<'i>(input: &mut &'i str) -> Option<Vec<Symbol<'i>>> {
+    use rayon::prelude::*;
+
+    // The table ends at the next `# ` header (e.g. `# Dead Stripped Symbols:`).
+    let end = if input.starts_with("# ") {
+        0
+    } else {
+        input.find("\n# ").map_or(input.len(), |i| i + 1)
+    };
+    let table = &input[..end];
+    if table.len() < PARALLEL_MIN_LEN {
+        return None;
+    }
+
+    let chunks: Option<Vec<Vec<Symbol<'i>>>> = line_chunks(table, CHUNK_LEN)
+        .into_par_iter()
+        .map(|mut chunk| {
+            let parsed = symbols(&mut chunk).ok()?;
+            chunk.is_empty().then_some(parsed)
+        })
+        .collect();
+    let symbols = chunks?.into_iter().flatten().collect();
+    *input = &input[end..];
+    Some(symbols)
+}
+
+/// Split `text` into pieces of at least `len` bytes that end on a line boundary.
+#[cfg(feature = "parallel")]
+fn line_chunks(text: &str, len: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let cut = rest.as_bytes()[len.min(rest.len())..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(rest.len(), |i| len + i + 1);
+        let (chunk, tail) = rest.split_at(cut);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+/// Parse `__` identifier (segment/section) like `__TEXT` / `__text`.
+fn parse_segment_or_section<'a>(i: &mut &'a str) -> ParserResult<&'a str> {
+    let start = *i;
+    let _ = preceded(
+        "__",
+        winnow::token::take_while(1.., |c: char| c.is_ascii_alphanumeric() || c == '_'),
+    )
+    .parse_next(i)?;
+    let consumed_len = start.len() - i.len();
+    Ok(&start[..consumed_len])
+}
+
+/// Parse one dead-stripped row (e.g. `<<dead>> \t0x00000018\t[  2] _unused`).
+pub(crate) fn dead_symbol<'i>(input: &mut &'i str) -> ParserResult<DeadSymbol<'i>> {
+    preceded(multispace0, literal("<<dead>>")).parse_next(input)?;
+    let size = preceded(multispace0, hex_number).parse_next(input)?;
+    let file_index = preceded(spaces, file_index).parse_next(input)?;
+    let name = preceded(spaces, till_line_ending).parse_next(input)?;
+
+    Ok(DeadSymbol {
+        size,
+        file_index,
+        name: Cow::Borrowed(name),
+    })
+}
+
+/// Parse the `# Dead Stripped Symbols:` block, if the map has one.
+fn dead_stripped_table<'i>(input: &mut &'i str) -> ParserResult<Vec<DeadSymbol<'i>>> {
+    *input = input.trim_start();
+    if !input.starts_with(MapFileHeaders::DeadStrippedSymbols.as_str()) {
+        return Ok(Vec::new());
+    }
+    terminated(header(MapFileHeaders::DeadStrippedSymbols), line_ending).parse_next(input)?;
+    terminated(till_line_ending, opt(line_ending)).parse_next(input)?;
+    repeat_till(
+        0..,
+        terminated(dead_symbol, opt(line_ending)),
+        preceded(multispace0, alt((peek(literal("# ")), eof))),
+    )
+    .parse_next(input)
+    .map(|(symbols, _)| symbols)
+}
+
+/// Parse one section row (e.g. `0xADDR\t0xSIZE\t__SEG\t__sect`).
+fn section<'i>(input: &mut &'i str) -> ParserResult<Section<'i>> {
+    let address = preceded(multispace0, hex_number).parse_next(input)?;
+    let size = preceded(multispace0, hex_number).parse_next(input)?;
+    spaces.parse_next(input)?;
+    let segment_name = parse_segment_or_section.parse_next(input)?;
+    spaces.parse_next(input)?;
+    let section_name = parse_segment_or_section.parse_next(input)?;
+
+    Ok(Section {
+        address,
+        size,
+        segment: Cow::Borrowed(segment_name),
+        section: Cow::Borrowed(section_name),
+        load_address: None,
+        alignment: None,
+        load_region: None,
+        execution_region: None,
+    })
+}
+
+/// Parse consecutive section rows until `# Symbols:` or EOF.
+fn sections<'i>(input: &mut &'i str) -> ParserResult<Vec<Section<'i>>> {
+    repeat_till(
+        0..,
+        terminated(section, opt(line_ending)),
+        alt((
+            peek(terminated(
+                preceded(spaces, literal(MapFileHeaders::Symbols.as_str())),
+                opt(line_ending),
+            )),
+            eof,
+        )),
+    )
+    .parse_next(input)
+    .map(|(sections, _)| sections)
+}
+
+/// Parse sections block after `# Sections:` header.
+fn section_table<'i>(input: &mut &'i str) -> ParserResult<Vec<Section<'i>>> {
+    delimited(
+        opt(line_ending),
+        header(MapFileHeaders::Sections),
+        line_ending,
+    )
+    .parse_next(input)?;
+    terminated(till_line_ending, line_ending).parse_next(input)?;
+    sections.parse_next(input)
+}
+
+/// Parse target path header (e.g. `# Path: /target/debug/deps/sample-app`).
+fn target_path<'i>(input: &mut &'i str) -> ParserResult<&'i str> {
+    preceded(
+        literal(MapFileHeaders::Path.as_str()),
+        preceded(multispace0, till_line_ending),
+    )
+    .context(StrContext::Label("Path"))
+    .context(StrContext::Expected(StrContextValue::Description(
+        "Expected a Path",
+    )))
+    .parse_next(input)
+}
+
+/// Returns true if the map starts with the ld64 `# Path:` header.
+pub(crate) fn sniff(head: &str) -> bool {
+    head.trim_start().starts_with(MapFileHeaders::Path.as_str())
+}
+
+/// Parse an ld64 map: Path -> Arch -> Object files -> Sections -> Symbols.
+pub(crate) fn parse<'i>(input: &mut &'i str) -> ParserResult<MapFile<'i>> {
+    *input = input.trim_start();
+
+    // Parse all fields first
+    let target_path = Cow::Borrowed(target_path(input)?);
+    let arch = Cow::Borrowed(arch.context(StrContext::Label("Arch")).parse_next(input)?);
+    let object_files = object_files
+        .context(StrContext::Label("Object files"))
+        .parse_next(input)?;
+    let sections = section_table
+        .context(StrContext::Label("Sections"))
+        .parse_next(input)?;
+    let symbols = symbol_table
+        .context(StrContext::Label("Symbols"))
+        .parse_next(input)?;
+    let dead_stripped = dead_stripped_table
+        .context(StrContext::Label("Dead Stripped Symbols"))
+        .parse_next(input)?;
+
+    // Detect binary format based on section names
+    let binary_format = detect_binary_format(&sections);
+
+    Ok(MapFile {
+        target_path,
+        arch,
+        object_files,
+        sections,
+        symbols,
+        dead_stripped,
+        binary_format,
+        memory_regions: Vec::new(),
+        dialect: MapDialect::Ld64,
+        invalid_utf8: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arch() {
+        let mut input = "# Arch: x86_64";
+        let result = arch(&mut input);
+        assert_eq!(result.unwrap(), "x86_64");
+    }
+
+    #[test]
+    fn test_object_files() {
+        let mut input = r"# Object files:
+[ 66] /Library/Developer/CommandLineTools/SDKs/MacOSX14.4.sdk/usr/lib/system/libunwind.tbd";
+        let result = object_files(&mut input);
+        let object_files = result.unwrap();
+
+        assert_eq!(object_files.len(), 1);
+        assert_eq!(object_files[0].index, 66);
+        assert_eq!(
+            object_files[0].path,
+            "/Library/Developer/CommandLineTools/SDKs/MacOSX14.4.sdk/usr/lib/system/libunwind.tbd"
+        );
+    }
+
+    #[test]
+    fn test_target_path() {
+        let mut input = "# Path: /target/debug/deps/sample-app";
+        let result = target_path(&mut input);
+        assert_eq!(result.unwrap(), "/target/debug/deps/sample-app");
+    }
+
+    #[test]
+    fn test_object_files_immediately_followed_by_sections() {
+        let mut input = r"# Object files:
+[  0] a.o
+[  1] b.o
+# Sections:
+# Address	Size    	Segment Section
+0x0	0x1	__TEXT	__text";
+        let result = object_files(&mut input).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].index, 0);
+        assert_eq!(result[0].path, "a.o");
+        // Remaining input should start with '# Sections:' now
+        assert!(input.starts_with("# Sections:"));
+    }
+
+    #[test]
+    fn test_symbol() {
+        let mut input = r"0x10004C058	0x00000018	[  1] __ZN3std3sys3pal4unix17thread_local_dtor13register_dtor5DTORS17hf7230a0b661819a4E";
+        let result = symbol(&mut input);
+        let symbol = result.unwrap();
+        assert_eq!(symbol.address, 0x10004C058);
+        assert_eq!(symbol.size, 0x00000018);
+        assert_eq!(symbol.file_index, 1);
+        assert_eq!(
+            symbol.name,
+            "__ZN3std3sys3pal4unix17thread_local_dtor13register_dtor5DTORS17hf7230a0b661819a4E"
+        );
+    }
+
+    #[test]
+    fn test_single_symbol_row() {
+        let mut input = r"0x10004C058	0x00000018	[  1] __ZN3std3sys3pal4unix17thread_local_dtor13register_dtor5DTORS17hf7230a0b661819a4E";
+        let result = symbols(&mut input);
+        let symbols = result.unwrap();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].address, 0x10004C058);
+    }
+
+    #[test]
+    fn test_multiple_symbol_rows() {
+        let mut input = r"
+        0x10004C058	0x00000018	[  1] __ZN3std3sys3pal4unix17thread_local_dtor13register_dtor5DTORS17hf7230a0b661819a4E
+        0x10004C059	0x00000020	[  2] __ZN3std3sys3pal4unix17thread_local_dtor13register_dtor5DTORS17hf7230a0b661819a4E";
+        let result = symbols(&mut input);
+        let symbols = result.unwrap();
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].address, 0x10004C058);
+        assert_eq!(symbols[1].address, 0x10004C059);
+    }
+
+    #[test]
+    fn test_symbol_table() {
+        let mut input = r"# Symbols:
+    # Address	Size    	File  Name
+    0x10004C058	0x00000018	[  1] __ZN3std3sys3pal4unix17thread_local_dtor13register_dtor5DTORS17hf7230a0b661819a4E";
+        let result = symbol_table(&mut input);
+        let symbols = result.unwrap();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].address, 0x10004C058);
+    }
+
+    #[test]
+    fn test_dead_stripped_table() {
+        let mut input = "# Symbols:\n# Address\tSize    \tFile  Name\n\
+                         0x100003F70\t0x00000010\t[  1] _main\n\
+                         # Dead Stripped Symbols:\n\
+                         #        \tSize    \tFile  Name\n\
+                         <<dead>> \t0x00000018\t[  1] _unused\n\
+                         <<dead>> \t0x00000006\t[  2] literal string: hello\n";
+        let symbols = symbol_table(&mut input).unwrap();
+        assert_eq!(symbols.len(), 1);
+        let dead = dead_stripped_table(&mut input).unwrap();
+        assert_eq!(dead.len(), 2);
+        assert_eq!(dead[0].size, 0x18);
+        assert_eq!(dead[0].file_index, 1);
+        assert_eq!(dead[0].name, "_unused");
+        assert_eq!(dead[1].name, "literal string: hello");
+        assert!(input.is_empty());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_line_chunks() {
+        let text = "aa\nbbbb\ncc\n\nd";
+        assert_eq!(line_chunks(text, 3), ["aa\nbbbb\n", "cc\n\n", "d"]);
+        assert_eq!(line_chunks(text, 100), [text]);
+        assert!(line_chunks("", 3).is_empty());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_par_symbols_matches_sequential() {
+        let mut table = String::new();
+        let mut address = 0x1_0000_0000_u64;
+        while table.len() < PARALLEL_MIN_LEN * 2 {
+            table.push_str(&format!(
+                "0x{address:X}\t0x00000010\t[  1] _sym_{address:x}\n"
+            ));
+            address += 0x10;
+        }
+        table.push_str("# Dead Stripped Symbols:\n");
+
+        let mut parallel = table.as_str();
+        let mut sequential = table.as_str();
+        let expected = symbols(&mut sequential).unwrap();
+        assert_eq!(par_symbols(&mut parallel), Some(expected));
+        assert_eq!(parallel, sequential);
+        assert_eq!(parallel, "# Dead Stripped Symbols:\n");
+
+        // A malformed row leaves the input for the sequential parser to report.
+        let middle = table[table.len() / 2..].find('\n').unwrap() + table.len() / 2 + 1;
+        let bad = format!("{}0x1010\toops\n{}", &table[..middle], &table[middle..]);
+        let mut input = bad.as_str();
+        assert_eq!(par_symbols(&mut input), None);
+        assert_eq!(input, bad);
+    }
+
+    #[test]
+    fn test_single_section_row() {
+        let mut input = r"0x10004C058	0x00000018	__TEXT	__text";
+        let result = section(&mut input);
+        let section = result.unwrap();
+        assert_eq!(section.address, 0x10004C058);
+        assert_eq!(section.size, 0x00000018);
+        assert_eq!(section.segment, "__TEXT");
+        assert_eq!(section.section, "__text");
+    }
+
+    #[test]
+    fn test_section_with_underscores() {
+        // Use actual tab characters, matching fixture formatting
+        let mut input = "0x10003BBB8\t0x000017F4\t__TEXT\t__gcc_except_tab";
+        let result = section(&mut input).unwrap();
+        assert_eq!(result.segment, "__TEXT");
+        assert_eq!(result.section, "__gcc_except_tab");
+    }
+
+    #[test]
+    fn test_sections() {
+        let mut input = r"0x10004C058	0x00000018	__TEXT	__text
+        0x10004C059	0x00000020	__TEXT	__text";
+        let result = sections(&mut input);
+        let sections = result.unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].address, 0x10004C058);
+        assert_eq!(sections[0].section, "__text");
+        assert_eq!(sections[0].segment, "__TEXT");
+        assert_eq!(sections[1].address, 0x10004C059);
+        assert_eq!(sections[1].section, "__text");
+        assert_eq!(sections[1].segment, "__TEXT");
+    }
+
+    #[test]
+    fn test_section_table() {
+        let mut input = r"# Sections:
+        # Address	Size    	Segment Section
+        0x10004C058	0x00000018	__TEXT	__text";
+        let result = section_table(&mut input);
+        let sections = result.unwrap();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].address, 0x10004C058);
+        assert_eq!(sections[0].size, 0x00000018);
+        assert_eq!(sections[0].segment, "__TEXT");
+        assert_eq!(sections[0].section, "__text");
+    }
+
+    #[test]
+    fn test_section_table_stops_at_symbols() {
+        let mut input = r"# Sections:
+    # Address	Size    	Segment Section
+    0x10004C058	0x00000018	__TEXT	__text
+    # Symbols:
+    0x10004C060	0x00000030	[  1] some_symbol";
+        let result = section_table(&mut input);
+        let sections = result.unwrap();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].address, 0x10004C058);
+    }
+}
@@ -1,70 +1,263 @@
-use std::path::Path;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
 use std::str;
 
-use serde::Serialize;
+use serde::{Serialize, Serializer};
 
 use winnow::{
-    ascii::{digit1, line_ending, multispace0, till_line_ending},
-    combinator::{alt, delimited, eof, opt, peek, preceded, repeat, repeat_till, terminated},
-    error::{StrContext, StrContextValue},
-    token::literal,
+    ascii::{line_ending, multispace0, till_line_ending},
+    combinator::{opt, preceded, terminated},
+    error::{ContextError, StrContext, StrContextValue},
+    stream::Offset,
     Parser, Result as ParserResult,
 };
 
+mod armlink;
+mod compression;
+mod error;
+mod gnu;
+mod gold;
+mod iar;
+mod kind;
+mod ld64;
+mod lld;
+mod mold;
+mod msvc;
+mod object;
+mod stream;
+mod ti;
+
+pub use error::{Error, ParseError};
+pub use kind::SymbolKind;
+pub use object::ObjectKind;
+pub use stream::SymbolStream;
+
 /// A symbol entry from the linker map file.
 ///
-/// Represents a single symbol with its address, size, source object file, and name.
-#[derive(Debug, Serialize)]
-pub struct Symbol {
-    pub address: String,
-    pub size: String,
-    pub file_index: String,
-    pub name: String,
+/// Represents a single symbol with its address, size, source object file, kind and name.
+/// Addresses and sizes serialize as hex strings (`0x1F0`).
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct Symbol<'a> {
+    #[serde(serialize_with = "serialize_hex")]
+    pub address: u64,
+    #[serde(serialize_with = "serialize_hex")]
+    pub size: u64,
+    /// Index of the defining object in [`MapFile::object_files`]
+    pub file_index: u32,
+    /// What the symbol is, assigned once the map's sections are known
+    pub kind: SymbolKind,
+    pub name: Cow<'a, str>,
+}
+
+impl Symbol<'_> {
+    /// Copy any borrowed name, detaching the symbol from the parsed input.
+    pub fn into_owned(self) -> Symbol<'static> {
+        Symbol {
+            address: self.address,
+            size: self.size,
+            file_index: self.file_index,
+            kind: self.kind,
+            name: Cow::Owned(self.name.into_owned()),
+        }
+    }
+}
+
+/// A symbol the linker removed as unreachable, listed by ld64 under
+/// `# Dead Stripped Symbols:` when linking with `-dead_strip`.
+///
+/// Dead symbols have no address; sizes serialize as hex strings (`0x1F0`).
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct DeadSymbol<'a> {
+    #[serde(serialize_with = "serialize_hex")]
+    pub size: u64,
+    /// Index of the defining object in [`MapFile::object_files`]
+    pub file_index: u32,
+    pub name: Cow<'a, str>,
+}
+
+impl DeadSymbol<'_> {
+    /// Copy any borrowed name, detaching the symbol from the parsed input.
+    pub fn into_owned(self) -> DeadSymbol<'static> {
+        DeadSymbol {
+            size: self.size,
+            file_index: self.file_index,
+            name: Cow::Owned(self.name.into_owned()),
+        }
+    }
 }
 
 /// A memory section entry from the linker map file.
 ///
 /// Represents a contiguous memory region with segment and section classifications
 /// (e.g., `__TEXT/__text` for Mach-O or `.text/.text` for ELF).
-#[derive(Debug, Serialize)]
-pub struct Section {
-    pub address: String,
-    pub size: String,
-    pub segment: String,
-    pub section: String,
-}
-
-#[derive(Debug)]
-enum MapFileHeaders {
-    Path,
-    Architecture,
-    ObjectFiles,
-    Symbols,
-    Sections,
-}
-
-#[cfg(target_os = "macos")]
-impl MapFileHeaders {
-    fn as_str(&self) -> &str {
+#[derive(Debug, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct Section<'a> {
+    #[serde(serialize_with = "serialize_hex")]
+    pub address: u64,
+    #[serde(serialize_with = "serialize_hex")]
+    pub size: u64,
+    pub segment: Cow<'a, str>,
+    pub section: Cow<'a, str>,
+    /// Load address (LMA) when it differs from the runtime address, as reported
+    /// by GNU ld (`load address 0x...`) and lld (`LMA` column)
+    #[serde(serialize_with = "serialize_opt_hex")]
+    pub load_address: Option<u64>,
+    /// Section alignment in bytes, when the map reports it
+    pub alignment: Option<u64>,
+    /// Region the section is stored in (e.g. armlink load region, GNU ld `AT> FLASH`)
+    pub load_region: Option<Cow<'a, str>>,
+    /// Region the section runs from (e.g. armlink execution region, GNU ld `> RAM`)
+    pub execution_region: Option<Cow<'a, str>>,
+}
+
+impl Section<'_> {
+    /// Copy any borrowed names, detaching the section from the parsed input.
+    pub fn into_owned(self) -> Section<'static> {
+        Section {
+            address: self.address,
+            size: self.size,
+            segment: owned(self.segment),
+            section: owned(self.section),
+            load_address: self.load_address,
+            alignment: self.alignment,
+            load_region: self.load_region.map(owned),
+            execution_region: self.execution_region.map(owned),
+        }
+    }
+}
+
+/// Linker map dialect, identified from the file contents at runtime.
+///
+/// Header recognition is driven by the dialect rather than the host OS, so an
+/// ld64 map produced on macOS parses the same way on Linux.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum MapDialect {
+    /// Apple ld64 / ld-prime (`# Path:`, `# Arch:`, `# Object files:` ...)
+    Ld64,
+    /// GNU ld / BFD (`-Map`)
+    GnuLd,
+    /// LLVM lld for ELF targets (`--Map`)
+    Lld,
+    /// mold (`-Map`)
+    Mold,
+    /// GNU gold (`--Map`)
+    Gold,
+    /// MSVC link.exe / lld-link (`/MAP`)
+    Msvc,
+    /// Arm Compiler armlink (`--map --symbols`)
+    Armlink,
+    /// IAR ILINK (`--map`)
+    Iar,
+    /// TI code generation tools linker (`--map_file`)
+    Ti,
+}
+
+/// Number of leading bytes inspected when sniffing the dialect.
+const SNIFF_LEN: usize = 8 * 1024;
+
+impl MapDialect {
+    /// Every dialect, in the order they are tried during detection.
+    ///
+    /// gold shares GNU ld's block layout, so its more specific titles are checked first.
+    pub const ALL: [MapDialect; 9] = [
+        MapDialect::Ld64,
+        MapDialect::Gold,
+        MapDialect::GnuLd,
+        MapDialect::Lld,
+        MapDialect::Mold,
+        MapDialect::Msvc,
+        MapDialect::Armlink,
+        MapDialect::Iar,
+        MapDialect::Ti,
+    ];
+
+    /// Returns the human-readable name of the dialect.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MapDialect::Ld64 => "Apple ld64",
+            MapDialect::GnuLd => "GNU ld",
+            MapDialect::Lld => "LLVM lld",
+            MapDialect::Mold => "mold",
+            MapDialect::Gold => "GNU gold",
+            MapDialect::Msvc => "MSVC link.exe",
+            MapDialect::Armlink => "Arm armlink",
+            MapDialect::Iar => "IAR ILINK",
+            MapDialect::Ti => "TI linker",
+        }
+    }
+
+    /// The header a map of this dialect is recognized by, for error messages.
+    fn signature(&self) -> &'static str {
+        match self {
+            MapDialect::Ld64 => "Apple ld64 (`# Path:`)",
+            MapDialect::GnuLd => "GNU ld (`Linker script and memory map`)",
+            MapDialect::Lld => "LLVM lld (`VMA LMA Size Align Out In Symbol`)",
+            MapDialect::Mold => "mold (`VMA Size Align Out In Symbol`)",
+            MapDialect::Gold => "GNU gold (`Memory map`)",
+            MapDialect::Msvc => "MSVC link.exe (`Start Length Name Class`)",
+            MapDialect::Armlink => "Arm armlink (`Memory Map of the image`)",
+            MapDialect::Iar => "IAR ILINK (`*** PLACEMENT SUMMARY`)",
+            MapDialect::Ti => "TI linker (`OUTPUT FILE NAME:`)",
+        }
+    }
+
+    /// Returns true if `head` looks like the start of a map in this dialect.
+    fn sniff(&self, head: &str) -> bool {
+        match self {
+            MapDialect::Ld64 => ld64::sniff(head),
+            MapDialect::GnuLd => gnu::sniff(head),
+            MapDialect::Lld => lld::sniff(head),
+            MapDialect::Mold => mold::sniff(head),
+            MapDialect::Gold => gold::sniff(head),
+            MapDialect::Msvc => msvc::sniff(head),
+            MapDialect::Armlink => armlink::sniff(head),
+            MapDialect::Iar => iar::sniff(head),
+            MapDialect::Ti => ti::sniff(head),
+        }
+    }
+
+    /// Detect the dialect from the first [`SNIFF_LEN`] bytes of the map.
+    pub fn detect(contents: &str) -> Option<Self> {
+        let mut end = contents.len().min(SNIFF_LEN);
+        while !contents.is_char_boundary(end) {
+            end -= 1;
+        }
+        let head = &contents[..end];
+        MapDialect::ALL
+            .into_iter()
+            .find(|dialect| dialect.sniff(head))
+    }
+
+    /// Parse `input` as a map of this dialect.
+    fn parse<'i>(&self, input: &mut &'i str) -> ParserResult<MapFile<'i>> {
         match self {
-            MapFileHeaders::Path => "# Path:",
-            MapFileHeaders::Architecture => "# Arch:",
-            MapFileHeaders::ObjectFiles => "# Object files:",
-            MapFileHeaders::Sections => "# Sections:",
-            MapFileHeaders::Symbols => "# Symbols:",
+            MapDialect::Ld64 => ld64::parse(input),
+            MapDialect::GnuLd => gnu::parse(input),
+            MapDialect::Lld => lld::parse(input),
+            MapDialect::Mold => mold::parse(input),
+            MapDialect::Gold => gold::parse(input),
+            MapDialect::Msvc => msvc::parse(input),
+            MapDialect::Armlink => armlink::parse(input),
+            MapDialect::Iar => iar::parse(input),
+            MapDialect::Ti => ti::parse(input),
         }
     }
 }
 
 /// Binary executable format detected from the linker map.
 ///
-/// Identifies whether the binary is Mach-O (macOS/iOS), ELF (Linux/BSD), or unknown.
+/// Identifies whether the binary is Mach-O (macOS/iOS), ELF (Linux/BSD), PE/COFF (Windows), or unknown.
 #[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
 pub enum BinaryFormat {
     /// Mach-O format (macOS, iOS) - sections prefixed with `__`
     MachO,
     /// ELF format (Linux, BSD) - sections prefixed with `.`
     Elf,
+    /// PE/COFF format (Windows) - identified by the map dialect
+    Pe,
     /// Unknown or unsupported format
     Unknown,
 }
@@ -75,6 +268,7 @@ impl BinaryFormat {
         match self {
             BinaryFormat::MachO => "Mach-O",
             BinaryFormat::Elf => "ELF",
+            BinaryFormat::Pe => "PE/COFF",
             BinaryFormat::Unknown => "Unknown",
         }
     }
@@ -83,36 +277,209 @@ impl BinaryFormat {
 /// An object file entry from the linker map.
 ///
 /// Represents a compiled object file or library that was linked into the final binary.
-#[derive(Debug, Serialize)]
-pub struct ObjectFile {
-    pub index: i32,
-    pub path: String,
+/// Objects extracted from static libraries are split into `archive` and `member`.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct ObjectFile<'a> {
+    pub index: u32,
+    pub path: Cow<'a, str>,
+    /// Static library the object was extracted from (`libfoo.a` in `libfoo.a[10](foo.o)`)
+    pub archive: Option<Cow<'a, str>>,
+    /// Name of the object within `archive` (`foo.o` in `libfoo.a[10](foo.o)`)
+    pub member: Option<Cow<'a, str>>,
+    /// Position of the member within `archive`, when the map reports it (ld64 only)
+    pub member_index: Option<u32>,
+    pub kind: ObjectKind,
+}
+
+impl<'a> ObjectFile<'a> {
+    /// Create the object file at `index`, decomposing `path` into archive and member.
+    pub fn new(index: u32, path: impl Into<Cow<'a, str>>) -> Self {
+        let path = path.into();
+        let parts = object::decompose(&path);
+        Self::from_parts(index, path, parts)
+    }
+
+    /// Like [`ObjectFile::new`], also splitting MSVC's `lib:object` paths.
+    pub(crate) fn new_msvc(index: u32, path: Cow<'a, str>) -> Self {
+        let parts = object::library_object(&path).unwrap_or_else(|| object::decompose(&path));
+        Self::from_parts(index, path, parts)
+    }
+
+    fn from_parts(index: u32, path: Cow<'a, str>, parts: object::PathParts) -> Self {
+        let slice = |range: std::ops::Range<usize>| match &path {
+            Cow::Borrowed(path) => Cow::Borrowed(&path[range]),
+            Cow::Owned(path) => Cow::Owned(path[range].to_string()),
+        };
+        ObjectFile {
+            index,
+            archive: parts.archive.map(slice),
+            member: parts.member.map(slice),
+            member_index: parts.member_index,
+            kind: parts.kind,
+            path,
+        }
+    }
+
+    /// The member name for archive members, otherwise the whole path.
+    pub fn display_name(&self) -> &str {
+        self.member.as_deref().unwrap_or(&self.path)
+    }
+
+    /// Copy a borrowed path, detaching the object file from the parsed input.
+    pub fn into_owned(self) -> ObjectFile<'static> {
+        ObjectFile {
+            index: self.index,
+            path: owned(self.path),
+            archive: self.archive.map(owned),
+            member: self.member.map(owned),
+            member_index: self.member_index,
+            kind: self.kind,
+        }
+    }
+}
+
+/// A memory region from a linker script `MEMORY` command.
+///
+/// Listed under `Memory Configuration` in GNU ld maps (e.g. `FLASH 0x08000000 0x00100000 xr`).
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct MemoryRegion<'a> {
+    pub name: Cow<'a, str>,
+    #[serde(serialize_with = "serialize_hex")]
+    pub origin: u64,
+    #[serde(serialize_with = "serialize_hex")]
+    pub length: u64,
+    pub attributes: Cow<'a, str>,
+}
+
+impl MemoryRegion<'_> {
+    /// Copy any borrowed names, detaching the region from the parsed input.
+    pub fn into_owned(self) -> MemoryRegion<'static> {
+        MemoryRegion {
+            name: owned(self.name),
+            origin: self.origin,
+            length: self.length,
+            attributes: owned(self.attributes),
+        }
+    }
 }
 
 /// The complete parsed linker map file.
 ///
 /// Contains all symbols, sections, object files, and metadata from a linker map file.
-#[derive(Debug, Serialize)]
-pub struct MapFile {
+/// Names borrow from the map contents when parsed with [`parse_str`] or from a
+/// [`MapContents`]; maps returned by [`parse`] and [`parse_reader`] copy them
+/// (`MapFile<'static>`).
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct MapFile<'a> {
     /// Target architecture (e.g., "arm64", "x86_64")
-    pub arch: String,
+    pub arch: Cow<'a, str>,
     /// List of object files that were linked
-    pub object_files: Vec<ObjectFile>,
+    pub object_files: Vec<ObjectFile<'a>>,
     /// Path to the output binary
-    pub target_path: String,
+    pub target_path: Cow<'a, str>,
     /// All symbols defined in the binary
-    pub symbols: Vec<Symbol>,
+    pub symbols: Vec<Symbol<'a>>,
+    /// Symbols removed by dead-stripping (ld64 `# Dead Stripped Symbols:`)
+    pub dead_stripped: Vec<DeadSymbol<'a>>,
     /// All memory sections in the binary
-    pub sections: Vec<Section>,
+    pub sections: Vec<Section<'a>>,
     /// Detected binary format
     pub binary_format: BinaryFormat,
+    /// Memory regions declared by the linker script (GNU ld `MEMORY`, TI `MEMORY CONFIGURATION`)
+    pub memory_regions: Vec<MemoryRegion<'a>>,
+    /// Linker dialect the map was parsed as
+    pub dialect: MapDialect,
+    /// Number of invalid UTF-8 sequences replaced with `U+FFFD` while reading the map
+    pub invalid_utf8: usize,
+}
+
+impl MapFile<'_> {
+    /// Copy every borrowed name, detaching the map from the parsed input.
+    pub fn into_owned(self) -> MapFile<'static> {
+        MapFile {
+            arch: owned(self.arch),
+            object_files: self
+                .object_files
+                .into_iter()
+                .map(ObjectFile::into_owned)
+                .collect(),
+            target_path: owned(self.target_path),
+            symbols: self.symbols.into_iter().map(Symbol::into_owned).collect(),
+            dead_stripped: self
+                .dead_stripped
+                .into_iter()
+                .map(DeadSymbol::into_owned)
+                .collect(),
+            sections: self.sections.into_iter().map(Section::into_owned).collect(),
+            binary_format: self.binary_format,
+            memory_regions: self
+                .memory_regions
+                .into_iter()
+                .map(MemoryRegion::into_owned)
+                .collect(),
+            dialect: self.dialect,
+            invalid_utf8: self.invalid_utf8,
+        }
+    }
 }
 
-fn read_file(map_file: &Path) -> String {
-    match std::fs::read_to_string(map_file) {
-        Ok(contents) => contents,
-        Err(e) => {
-            panic!("Error reading file: {:?}", e);
+fn owned(value: Cow<'_, str>) -> Cow<'static, str> {
+    Cow::Owned(value.into_owned())
+}
+
+/// Serialize a numeric address or size as hex; see [`format_hex`].
+fn serialize_hex<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_hex(*value))
+}
+
+fn serialize_opt_hex<S: Serializer>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serialize_hex(value, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Name used in errors for maps parsed from memory or a reader.
+const INPUT_NAME: &str = "<input>";
+
+/// Read a map file; see [`decode`].
+fn read_file(map_file: &Path) -> Result<(String, usize), Error> {
+    let bytes = std::fs::read(map_file).map_err(|e| Error::io(map_file, e))?;
+    decode(map_file, bytes)
+}
+
+/// Read a map from `reader`; see [`decode`].
+fn read_input(mut reader: impl Read) -> Result<(String, usize), Error> {
+    let name = Path::new(INPUT_NAME);
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|e| Error::io(name, e))?;
+    decode(name, bytes)
+}
+
+/// Decode map bytes, decompressing them first and replacing invalid UTF-8 lossily.
+///
+/// Returns the contents and the number of invalid sequences replaced with `U+FFFD`.
+fn decode(name: &Path, bytes: Vec<u8>) -> Result<(String, usize), Error> {
+    let bytes = compression::decompress(name, bytes)?;
+    match String::from_utf8(bytes) {
+        Ok(contents) => Ok((contents, 0)),
+        Err(error) => {
+            let bytes = error.as_bytes();
+            // NUL bytes mean a binary (e.g. the linked executable) rather than
+            // a map with Latin-1 paths.
+            if bytes.contains(&0) {
+                return Err(Error::InvalidUtf8 {
+                    path: name.to_path_buf(),
+                    offset: error.utf8_error().valid_up_to(),
+                });
+            }
+            let replaced = bytes
+                .utf8_chunks()
+                .filter(|chunk| !chunk.invalid().is_empty())
+                .count();
+            Ok((String::from_utf8_lossy(bytes).into_owned(), replaced))
         }
     }
 }
@@ -122,18 +489,6 @@ fn spaces<'i>(input: &mut &'i str) -> ParserResult<&'i str> {
     multispace0(input)
 }
 
-/// Parse architecture header (e.g. `# Arch: arm64` -> `arm64`).
-fn arch<'i>(input: &mut &'i str) -> ParserResult<&'i str> {
-    preceded(
-        preceded(
-            opt(line_ending),
-            preceded(literal(MapFileHeaders::Architecture.as_str()), multispace0),
-        ),
-        till_line_ending,
-    )
-    .parse_next(input)
-}
-
 /// Parse hex value starting `0x` (e.g. `0x1000007DC`).
 fn hex_value<'i>(input: &mut &'i str) -> ParserResult<&'i str> {
     preceded("0x", winnow::ascii::hex_digit1)
@@ -141,147 +496,150 @@ fn hex_value<'i>(input: &mut &'i str) -> ParserResult<&'i str> {
         .parse_next(input)
 }
 
-/// Parse one object file line (e.g. `[ 66] /path/libunwind.tbd`).
-fn object_file(input: &mut &str) -> ParserResult<ObjectFile> {
-    let (index, path): (&str, &str) = (
-        delimited('[', preceded(multispace0, digit1), ']'),
-        preceded(multispace0, till_line_ending),
-    )
-        .parse_next(input)?;
+/// Parse a `0x`-prefixed hex number (e.g. `0x1000007DC` -> `0x1000007DC_u64`).
+fn hex_number(input: &mut &str) -> ParserResult<u64> {
+    hex_value.verify_map(parse_hex).parse_next(input)
+}
 
-    // Parse the index; digit1 ensures it's valid digits
-    let index_num = index
-        .parse()
-        .expect("digit1 parser guarantees valid integer");
+/// Split the next line off the input, without its line ending.
+fn next_line<'i>(input: &mut &'i str) -> Option<&'i str> {
+    if input.is_empty() {
+        return None;
+    }
+    terminated(till_line_ending::<_, ContextError>, opt(line_ending))
+        .parse_next(input)
+        .ok()
+}
 
-    Ok(ObjectFile {
-        index: index_num,
-        path: path.to_string(),
-    })
+/// Error for a table row that does not parse, so a corrupt or truncated map
+/// fails instead of yielding a partial [`MapFile`].
+///
+/// `start` is the input the row was split off and `rest` what is left of the
+/// row where parsing stopped; `input` is rewound to `rest` so the error
+/// points into the row.
+fn invalid_row<'i>(
+    input: &mut &'i str,
+    start: &'i str,
+    rest: &str,
+    label: &'static str,
+) -> ContextError {
+    *input = &start[rest.offset_from(&start)..];
+    let mut error = ContextError::new();
+    error.push(StrContext::Label(label));
+    error
 }
 
-/// Parse entire object files block after `# Object files:` until next header.
-fn object_files(input: &mut &str) -> ParserResult<Vec<ObjectFile>> {
-    delimited(
-        opt(line_ending),
-        literal(MapFileHeaders::ObjectFiles.as_str()),
-        line_ending,
-    )
-    .parse_next(input)?;
-
-    repeat(
-        0..,
-        terminated(preceded(peek('['), object_file), opt(line_ending)),
-    )
-    .parse_next(input)
-}
-
-/// Parse one symbol row (e.g. `0xADDR\t0xSIZE\t[  1] name`).
-fn symbol(input: &mut &str) -> ParserResult<Symbol> {
-    let address = preceded(multispace0, hex_value).parse_next(input)?;
-    let size = preceded(multispace0, hex_value).parse_next(input)?;
-    let file_index = preceded(
-        preceded(opt(line_ending), spaces),
-        delimited('[', preceded(multispace0, digit1), ']'),
-    )
-    .parse_next(input)?;
-    let name = preceded(spaces, till_line_ending).parse_next(input)?;
-
-    Ok(Symbol {
-        address: address.to_string(),
-        size: size.to_string(),
-        file_index: file_index.to_string(),
-        name: name.to_string(),
-    })
+/// Numeric value of a hex literal, with or without the `0x` prefix.
+fn parse_hex(value: &str) -> Option<u64> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u64::from_str_radix(digits, 16).ok()
 }
 
-/// Parse consecutive symbol rows until header or EOF.
-fn symbols(input: &mut &str) -> ParserResult<Vec<Symbol>> {
-    repeat_till(
-        0..,
-        terminated(symbol, opt(line_ending)),
-        alt((literal("# "), eof)),
-    )
-    .parse_next(input)
-    .map(|(symbols, _)| symbols)
-}
-
-/// Parse full symbol table after `# Symbols:`.
-fn symbol_table(input: &mut &str) -> ParserResult<Vec<Symbol>> {
-    terminated(literal(MapFileHeaders::Symbols.as_str()), line_ending).parse_next(input)?;
-    terminated(till_line_ending, line_ending).parse_next(input)?;
-    symbols.parse_next(input)
-}
-
-/// Parse `__` identifier (segment/section) like `__TEXT` / `__text`.
-fn parse_segment_or_section<'a>(i: &mut &'a str) -> ParserResult<&'a str> {
-    let start = *i;
-    let _ = preceded(
-        "__",
-        winnow::token::take_while(1.., |c: char| c.is_ascii_alphanumeric() || c == '_'),
-    )
-    .parse_next(i)?;
-    let consumed_len = start.len() - i.len();
-    Ok(&start[..consumed_len])
-}
-
-/// Parse one section row (e.g. `0xADDR\t0xSIZE\t__SEG\t__sect`).
-fn section(input: &mut &str) -> ParserResult<Section> {
-    let address = preceded(multispace0, hex_value).parse_next(input)?;
-    let size = preceded(multispace0, hex_value).parse_next(input)?;
-    spaces.parse_next(input)?;
-    let segment_name = parse_segment_or_section.parse_next(input)?;
-    spaces.parse_next(input)?;
-    let section_name = parse_segment_or_section.parse_next(input)?;
-
-    Ok(Section {
-        address: address.to_string(),
-        size: size.to_string(),
-        segment: segment_name.to_string(),
-        section: section_name.to_string(),
-    })
+/// Render a numeric address or size the way ld64 maps spell them (`0x1F0`).
+fn format_hex(value: u64) -> String {
+    format!("0x{:X}", value)
 }
 
-/// Parse consecutive section rows until `# Symbols:` or EOF.
-fn sections(input: &mut &str) -> ParserResult<Vec<Section>> {
-    repeat_till(
-        0..,
-        terminated(section, opt(line_ending)),
-        alt((
-            peek(terminated(
-                preceded(spaces, literal(MapFileHeaders::Symbols.as_str())),
-                opt(line_ending),
-            )),
-            eof,
-        )),
-    )
-    .parse_next(input)
-    .map(|(sections, _)| sections)
-}
-
-/// Parse sections block after `# Sections:` header.
-fn section_table(input: &mut &str) -> ParserResult<Vec<Section>> {
-    delimited(
-        opt(line_ending),
-        literal(MapFileHeaders::Sections.as_str()),
-        line_ending,
-    )
-    .parse_next(input)?;
-    terminated(till_line_ending, line_ending).parse_next(input)?;
-    sections.parse_next(input)
-}
-
-/// Parse target path header (e.g. `# Path: /target/debug/deps/sample-app`).
-fn target_path<'i>(input: &mut &'i str) -> ParserResult<&'i str> {
-    preceded(
-        literal(MapFileHeaders::Path.as_str()),
-        preceded(multispace0, till_line_ending),
-    )
-    .context(StrContext::Label("Path"))
-    .context(StrContext::Expected(StrContextValue::Description(
-        "Expected a Path",
-    )))
-    .parse_next(input)
+/// Assigns stable indices to object files for dialects that reference inputs by path.
+#[derive(Default)]
+struct ObjectTable<'i> {
+    files: Vec<ObjectFile<'i>>,
+    lookup: HashMap<Cow<'i, str>, u32>,
+}
+
+impl<'i> ObjectTable<'i> {
+    fn intern(&mut self, path: impl Into<Cow<'i, str>>) -> u32 {
+        let path = path.into();
+        if let Some(&index) = self.lookup.get(&path) {
+            return index;
+        }
+        let index = self.files.len() as u32;
+        self.lookup.insert(path.clone(), index);
+        self.files.push(ObjectFile::new(index, path));
+        index
+    }
+
+    fn into_files(self) -> Vec<ObjectFile<'i>> {
+        self.files
+    }
+}
+
+/// An input section together with the symbols listed inside it.
+///
+/// ELF-style maps often print symbols without sizes, so a symbol with no known size
+/// is sized up to the next symbol (or the end of the section). Bytes in front of the
+/// first symbol are attributed to a symbol named after the input section itself.
+struct InputSection<'i> {
+    name: &'i str,
+    address: u64,
+    size: u64,
+    file_index: u32,
+    symbols: Vec<(u64, Option<u64>, &'i str)>,
+}
+
+impl<'i> InputSection<'i> {
+    fn push_symbols(mut self, out: &mut Vec<Symbol<'i>>) {
+        let end = self.address.saturating_add(self.size);
+        self.symbols
+            .retain(|&(address, _, _)| address >= self.address && address <= end);
+        self.symbols.sort_by_key(|&(address, _, _)| address);
+
+        let mut emit = |address: u64, size: u64, name: &'i str| {
+            out.push(Symbol {
+                address,
+                size,
+                file_index: self.file_index,
+                kind: SymbolKind::Data,
+                name: Cow::Borrowed(name),
+            });
+        };
+
+        let first = self.symbols.first().map_or(end, |&(address, _, _)| address);
+        if first > self.address {
+            emit(self.address, first - self.address, self.name);
+        }
+        for (position, &(address, size, name)) in self.symbols.iter().enumerate() {
+            let next = self
+                .symbols
+                .get(position + 1)
+                .map_or(end, |&(next, _, _)| next);
+            emit(address, size.unwrap_or(next - address), name);
+        }
+    }
+}
+
+/// Distribute symbols over the input sections that contain them.
+///
+/// Used by dialects that list symbols in a table of their own rather than under
+/// the input section that defines them.
+fn attach_symbols<'i>(
+    sections: &mut [InputSection<'i>],
+    mut symbols: Vec<(u64, Option<u64>, &'i str)>,
+) {
+    sections.sort_by_key(|section| section.address);
+    symbols.sort_by_key(|&(address, _, _)| address);
+    symbols.dedup_by_key(|&mut (address, _, name)| (address, name));
+    for symbol in symbols {
+        let position = sections.partition_point(|section| section.address <= symbol.0);
+        let Some(section) = position.checked_sub(1).map(|p| &mut sections[p]) else {
+            continue;
+        };
+        if symbol.0 < section.address.saturating_add(section.size) {
+            section.symbols.push(symbol);
+        }
+    }
+}
+
+/// ELF sections that are not loaded at runtime; kept as sections but not as symbols.
+fn is_non_alloc_section(name: &str) -> bool {
+    name.starts_with(".debug")
+        || name.starts_with(".zdebug")
+        || name.starts_with(".stab")
+        || name.starts_with(".comment")
+        || name.ends_with("attributes")
 }
 
 /// Detect binary format based on section names.
@@ -305,176 +663,178 @@ fn detect_binary_format(sections: &[Section]) -> BinaryFormat {
     }
 }
 
-/// Parse whole map: Path -> Arch -> Object files -> Sections -> Symbols.
-pub fn parse(map_file: &Path) -> ParserResult<MapFile> {
-    let contents = read_file(map_file);
-    let mut input = contents.as_str();
+/// A map read into memory, decompressed and decoded, ready to be parsed.
+///
+/// Keep it alive alongside the parsed [`MapFile`] so names can borrow from it
+/// instead of being copied one `String` at a time.
+#[derive(Debug, Clone)]
+pub struct MapContents {
+    path: PathBuf,
+    contents: String,
+    invalid_utf8: usize,
+}
+
+impl MapContents {
+    /// Parse the contents, dispatching on the detected dialect.
+    pub fn parse(&self) -> Result<MapFile<'_>, Error> {
+        parse_contents(&self.path, &self.contents, None, self.invalid_utf8)
+    }
 
-    // Parse all fields first
-    let target_path = target_path(&mut input)?.to_string();
-    let arch = arch(&mut input)?.to_string();
-    let object_files = object_files(&mut input)?;
-    let sections = section_table(&mut input)?;
-    let symbols = symbol_table(&mut input)?;
+    /// Parse the contents as the given dialect, skipping detection.
+    pub fn parse_as(&self, dialect: MapDialect) -> Result<MapFile<'_>, Error> {
+        parse_contents(&self.path, &self.contents, Some(dialect), self.invalid_utf8)
+    }
 
-    // Detect binary format based on section names
-    let binary_format = detect_binary_format(&sections);
+    /// The decoded map text.
+    pub fn as_str(&self) -> &str {
+        &self.contents
+    }
+}
 
-    Ok(MapFile {
-        target_path,
-        arch,
-        object_files,
-        sections,
-        symbols,
-        binary_format,
+/// Read a map file into memory for [`MapContents::parse`].
+pub fn load(map_file: &Path) -> Result<MapContents, Error> {
+    let (contents, invalid_utf8) = read_file(map_file)?;
+    Ok(MapContents {
+        path: map_file.to_path_buf(),
+        contents,
+        invalid_utf8,
     })
 }
 
-#[cfg(test)]
-mod tests {
+/// Read a map to the end from `reader` (e.g. stdin) for [`MapContents::parse`].
+pub fn load_reader(reader: impl Read) -> Result<MapContents, Error> {
+    let (contents, invalid_utf8) = read_input(reader)?;
+    Ok(MapContents {
+        path: PathBuf::from(INPUT_NAME),
+        contents,
+        invalid_utf8,
+    })
+}
 
-    use super::*;
+/// Returns true if the file at `path` is a gzip-, zstd- or xz-compressed map,
+/// judging by its magic bytes rather than its extension.
+///
+/// Unreadable files count as uncompressed; [`parse`] reports why they cannot be read.
+pub fn is_compressed(path: &Path) -> bool {
+    compression::is_compressed_file(path)
+}
 
-    #[test]
-    fn test_arch() {
-        let mut input = "# Arch: x86_64";
-        let result = arch(&mut input);
-        assert_eq!(result.unwrap(), "x86_64");
-    }
+/// Parse whole map, dispatching on the detected dialect.
+///
+/// Every name is copied out of the file; use [`load`] to borrow them instead.
+pub fn parse(map_file: &Path) -> Result<MapFile<'static>, Error> {
+    load(map_file)?.parse().map(MapFile::into_owned)
+}
 
-    #[test]
-    fn test_object_files() {
-        let mut input = r"# Object files:
-[ 66] /Library/Developer/CommandLineTools/SDKs/MacOSX14.4.sdk/usr/lib/system/libunwind.tbd";
-        let result = object_files(&mut input);
-        let object_files = result.unwrap();
-
-        assert_eq!(object_files.len(), 1);
-        assert_eq!(object_files[0].index, 66);
-        assert_eq!(
-            object_files[0].path,
-            "/Library/Developer/CommandLineTools/SDKs/MacOSX14.4.sdk/usr/lib/system/libunwind.tbd"
-        );
-    }
+/// Parse whole map as the given dialect, skipping detection.
+pub fn parse_as(map_file: &Path, dialect: MapDialect) -> Result<MapFile<'static>, Error> {
+    load(map_file)?.parse_as(dialect).map(MapFile::into_owned)
+}
 
-    #[test]
-    fn test_target_path() {
-        let mut input = "# Path: /target/debug/deps/sample-app";
-        let result = target_path(&mut input);
-        assert_eq!(result.unwrap(), "/target/debug/deps/sample-app");
-    }
+/// Parse map contents already in memory, dispatching on the detected dialect.
+///
+/// Names in the returned map borrow from `contents` rather than being copied.
+pub fn parse_str(contents: &str) -> Result<MapFile<'_>, Error> {
+    parse_contents(Path::new(INPUT_NAME), contents, None, 0)
+}
 
-    #[test]
-    fn test_object_files_immediately_followed_by_sections() {
-        let mut input = r"# Object files:
-[  0] a.o
-[  1] b.o
-# Sections:
-# Address	Size    	Segment Section
-0x0	0x1	__TEXT	__text";
-        let result = object_files(&mut input).unwrap();
-        assert_eq!(result.len(), 2);
-        assert_eq!(result[0].index, 0);
-        assert_eq!(result[0].path, "a.o");
-        // Remaining input should start with '# Sections:' now
-        assert!(input.starts_with("# Sections:"));
-    }
+/// Parse map contents already in memory as the given dialect.
+pub fn parse_str_as(contents: &str, dialect: MapDialect) -> Result<MapFile<'_>, Error> {
+    parse_contents(Path::new(INPUT_NAME), contents, Some(dialect), 0)
+}
 
-    #[test]
-    fn test_symbol() {
-        let mut input = r"0x10004C058	0x00000018	[  1] __ZN3std3sys3pal4unix17thread_local_dtor13register_dtor5DTORS17hf7230a0b661819a4E";
-        let result = symbol(&mut input);
-        let symbol = result.unwrap();
-        assert_eq!(symbol.address, "0x10004C058");
-        assert_eq!(symbol.size, "0x00000018");
-        assert_eq!(symbol.file_index, "1");
-        assert_eq!(
-            symbol.name,
-            "__ZN3std3sys3pal4unix17thread_local_dtor13register_dtor5DTORS17hf7230a0b661819a4E"
-        );
-    }
+/// Parse a map read to the end from `reader` (e.g. stdin), dispatching on the detected dialect.
+///
+/// Every name is copied out of the input; use [`load_reader`] to borrow them instead.
+pub fn parse_reader(reader: impl Read) -> Result<MapFile<'static>, Error> {
+    load_reader(reader)?.parse().map(MapFile::into_owned)
+}
 
-    #[test]
-    fn test_single_symbol_row() {
-        let mut input = r"0x10004C058	0x00000018	[  1] __ZN3std3sys3pal4unix17thread_local_dtor13register_dtor5DTORS17hf7230a0b661819a4E";
-        let result = symbols(&mut input);
-        let symbols = result.unwrap();
-        assert_eq!(symbols.len(), 1);
-        assert_eq!(symbols[0].address, "0x10004C058");
-    }
+/// Parse a map read to the end from `reader` as the given dialect.
+pub fn parse_reader_as(reader: impl Read, dialect: MapDialect) -> Result<MapFile<'static>, Error> {
+    load_reader(reader)?
+        .parse_as(dialect)
+        .map(MapFile::into_owned)
+}
 
-    #[test]
-    fn test_multiple_symbol_rows() {
-        let mut input = r"
-        0x10004C058	0x00000018	[  1] __ZN3std3sys3pal4unix17thread_local_dtor13register_dtor5DTORS17hf7230a0b661819a4E
-        0x10004C059	0x00000020	[  2] __ZN3std3sys3pal4unix17thread_local_dtor13register_dtor5DTORS17hf7230a0b661819a4E";
-        let result = symbols(&mut input);
-        let symbols = result.unwrap();
-        assert_eq!(symbols.len(), 2);
-        assert_eq!(symbols[0].address, "0x10004C058");
-        assert_eq!(symbols[1].address, "0x10004C059");
-    }
+/// Stream the symbols of a map, dispatching on the detected dialect; see [`SymbolStream`].
+pub fn stream(map_file: &Path) -> Result<SymbolStream<'static>, Error> {
+    let file = File::open(map_file).map_err(|e| Error::io(map_file, e))?;
+    SymbolStream::new(map_file, BufReader::new(file), None)
+}
 
-    #[test]
-    fn test_symbol_table() {
-        let mut input = r"# Symbols:
-    # Address	Size    	File  Name
-    0x10004C058	0x00000018	[  1] __ZN3std3sys3pal4unix17thread_local_dtor13register_dtor5DTORS17hf7230a0b661819a4E";
-        let result = symbol_table(&mut input);
-        let symbols = result.unwrap();
-        assert_eq!(symbols.len(), 1);
-        assert_eq!(symbols[0].address, "0x10004C058");
-    }
+/// Stream the symbols of a map as the given dialect.
+pub fn stream_as(map_file: &Path, dialect: MapDialect) -> Result<SymbolStream<'static>, Error> {
+    let file = File::open(map_file).map_err(|e| Error::io(map_file, e))?;
+    SymbolStream::new(map_file, BufReader::new(file), Some(dialect))
+}
 
-    #[test]
-    fn test_single_section_row() {
-        let mut input = r"0x10004C058	0x00000018	__TEXT	__text";
-        let result = section(&mut input);
-        let section = result.unwrap();
-        assert_eq!(section.address, "0x10004C058");
-        assert_eq!(section.size, "0x00000018");
-        assert_eq!(section.segment, "__TEXT");
-        assert_eq!(section.section, "__text");
-    }
+/// Stream the symbols of a map read from `reader` (e.g. stdin), dispatching on the detected dialect.
+pub fn stream_reader<'r>(reader: impl Read + 'r) -> Result<SymbolStream<'r>, Error> {
+    SymbolStream::new(Path::new(INPUT_NAME), BufReader::new(reader), None)
+}
 
-    #[test]
-    fn test_section_with_underscores() {
-        // Use actual tab characters, matching fixture formatting
-        let mut input = "0x10003BBB8\t0x000017F4\t__TEXT\t__gcc_except_tab";
-        let result = section(&mut input).unwrap();
-        assert_eq!(result.segment, "__TEXT");
-        assert_eq!(result.section, "__gcc_except_tab");
-    }
+/// Stream the symbols of a map read from `reader` as the given dialect.
+pub fn stream_reader_as<'r>(
+    reader: impl Read + 'r,
+    dialect: MapDialect,
+) -> Result<SymbolStream<'r>, Error> {
+    SymbolStream::new(Path::new(INPUT_NAME), BufReader::new(reader), Some(dialect))
+}
 
-    #[test]
-    fn test_sections() {
-        let mut input = r"0x10004C058	0x00000018	__TEXT	__text
-        0x10004C059	0x00000020	__TEXT	__text";
-        let result = sections(&mut input);
-        let sections = result.unwrap();
-        assert_eq!(sections.len(), 2);
-        assert_eq!(sections[0].address, "0x10004C058");
-        assert_eq!(sections[0].section, "__text");
-        assert_eq!(sections[0].segment, "__TEXT");
-        assert_eq!(sections[1].address, "0x10004C059");
-        assert_eq!(sections[1].section, "__text");
-        assert_eq!(sections[1].segment, "__TEXT");
+/// Error for a map no dialect recognizes, listing each candidate's signature.
+fn unknown_dialect(name: &Path, contents: &str) -> Error {
+    let mut error = ContextError::new();
+    error.push(StrContext::Label("map dialect"));
+    for candidate in MapDialect::ALL {
+        error.push(StrContext::Expected(StrContextValue::Description(
+            candidate.signature(),
+        )));
     }
+    ParseError::new(name, contents, 0, &error).into()
+}
+
+/// Run the dialect's parser (detecting it unless given), locating any failure within `contents`.
+fn parse_contents<'i>(
+    name: &Path,
+    contents: &'i str,
+    dialect: Option<MapDialect>,
+    invalid_utf8: usize,
+) -> Result<MapFile<'i>, Error> {
+    let Some(dialect) = dialect.or_else(|| MapDialect::detect(contents)) else {
+        return Err(unknown_dialect(name, contents));
+    };
+
+    let mut input = contents;
+    let mut map = dialect
+        .parse(&mut input)
+        .map_err(|error| ParseError::new(name, contents, input.offset_from(&contents), &error))?;
+    kind::classify_symbols(&mut map);
+    map.invalid_utf8 = invalid_utf8;
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
 
     #[test]
     fn test_binary_format_detection_macho() {
         let sections = vec![
             Section {
-                address: "0x1000007DC".to_string(),
-                size: "0x00036FC4".to_string(),
-                segment: "__TEXT".to_string(),
-                section: "__text".to_string(),
+                address: 0x1000007DC,
+                size: 0x00036FC4,
+                segment: "__TEXT".into(),
+                section: "__text".into(),
+                ..Default::default()
             },
             Section {
-                address: "0x100048000".to_string(),
-                size: "0x00000208".to_string(),
-                segment: "__DATA".to_string(),
-                section: "__data".to_string(),
+                address: 0x100048000,
+                size: 0x00000208,
+                segment: "__DATA".into(),
+                section: "__data".into(),
+                ..Default::default()
             },
         ];
         assert_eq!(detect_binary_format(&sections), BinaryFormat::MachO);
@@ -484,48 +844,23 @@ mod tests {
     fn test_binary_format_detection_elf() {
         let sections = vec![
             Section {
-                address: "0x1000".to_string(),
-                size: "0x1234".to_string(),
-                segment: ".text".to_string(),
-                section: ".text".to_string(),
+                address: 0x1000,
+                size: 0x1234,
+                segment: ".text".into(),
+                section: ".text".into(),
+                ..Default::default()
             },
             Section {
-                address: "0x2000".to_string(),
-                size: "0x5678".to_string(),
-                segment: ".data".to_string(),
-                section: ".data".to_string(),
+                address: 0x2000,
+                size: 0x5678,
+                segment: ".data".into(),
+                section: ".data".into(),
+                ..Default::default()
             },
         ];
         assert_eq!(detect_binary_format(&sections), BinaryFormat::Elf);
     }
 
-    #[test]
-    fn test_section_table() {
-        let mut input = r"# Sections:
-        # Address	Size    	Segment Section
-        0x10004C058	0x00000018	__TEXT	__text";
-        let result = section_table(&mut input);
-        let sections = result.unwrap();
-        assert_eq!(sections.len(), 1);
-        assert_eq!(sections[0].address, "0x10004C058");
-        assert_eq!(sections[0].size, "0x00000018");
-        assert_eq!(sections[0].segment, "__TEXT");
-        assert_eq!(sections[0].section, "__text");
-    }
-
-    #[test]
-    fn test_section_table_stops_at_symbols() {
-        let mut input = r"# Sections:
-    # Address	Size    	Segment Section
-    0x10004C058	0x00000018	__TEXT	__text
-    # Symbols:
-    0x10004C060	0x00000030	[  1] some_symbol";
-        let result = section_table(&mut input);
-        let sections = result.unwrap();
-        assert_eq!(sections.len(), 1);
-        assert_eq!(sections[0].address, "0x10004C058");
-    }
-
     #[test]
     fn test_parse_small_sample() {
         let map_file = Path::new("tests/fixtures/sample.map");
@@ -550,4 +885,91 @@ mod tests {
         assert_eq!(result.sections.len(), 14);
         assert!(!result.symbols.is_empty());
     }
+
+    #[test]
+    fn test_detect_dialect() {
+        assert_eq!(
+            MapDialect::detect("# Path: /target/debug/app\n# Arch: arm64\n"),
+            Some(MapDialect::Ld64)
+        );
+        assert_eq!(
+            MapDialect::detect("\nMemory Configuration\n"),
+            Some(MapDialect::GnuLd)
+        );
+        assert_eq!(MapDialect::detect("not a linker map"), None);
+    }
+
+    #[test]
+    fn test_detect_dialect_only_sniffs_head() {
+        let mut contents = "x".repeat(SNIFF_LEN);
+        contents.push_str("\n# Path: /target/debug/app\n");
+        assert_eq!(MapDialect::detect(&contents), None);
+        // A multi-byte character straddling the cut-off must not panic.
+        let contents = format!("{}é", "x".repeat(SNIFF_LEN - 1));
+        assert_eq!(MapDialect::detect(&contents), None);
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_dialect() {
+        let dir = std::env::temp_dir().join("linkerland-parser-unknown-dialect.map");
+        std::fs::write(&dir, "not a linker map\n").unwrap();
+        let result = parse(&dir);
+        std::fs::remove_file(&dir).ok();
+        let error = result.unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("map dialect"));
+        for candidate in MapDialect::ALL {
+            assert!(message.contains(candidate.as_str()), "{message}");
+        }
+    }
+
+    #[test]
+    fn test_parse_error_points_at_offending_line() {
+        let dir = std::env::temp_dir().join("linkerland-parser-bad-sections.map");
+        std::fs::write(
+            &dir,
+            "# Path: /target/debug/app\n# Arch: arm64\n# Object files:\n[  0] linker synthesized\n# Sectoins:\n",
+        )
+        .unwrap();
+        let result = parse(&dir);
+        std::fs::remove_file(&dir).ok();
+        let Err(Error::Parse(error)) = result else {
+            panic!("expected a parse error: {result:?}");
+        };
+        assert_eq!(error.path, dir);
+        assert_eq!(error.line, 5);
+        assert_eq!(error.label, Some("Sections"));
+        assert_eq!(error.snippet, "# Sectoins:");
+    }
+
+    #[test]
+    fn test_parse_missing_file() {
+        let path = std::env::temp_dir().join("linkerland-parser-missing.map");
+        assert!(matches!(parse(&path), Err(Error::NotFound(p)) if p == path));
+    }
+
+    #[test]
+    fn test_parse_decodes_latin1_lossily() {
+        let path = std::env::temp_dir().join("linkerland-parser-latin1.map");
+        let mut contents = b"# Path: /home/Jos\xe9/app\n# Arch: arm64\n# Object files:\n".to_vec();
+        contents.extend_from_slice(
+            b"[  0] /home/Jos\xe9/main.o\n# Sections:\n# Address\tSize\tSegment\tSection\n",
+        );
+        contents.extend_from_slice(b"# Symbols:\n# Address\tSize\tFile\tName\n");
+        std::fs::write(&path, contents).unwrap();
+        let result = parse(&path);
+        std::fs::remove_file(&path).ok();
+        let map = result.unwrap();
+        assert_eq!(map.invalid_utf8, 2);
+        assert_eq!(map.target_path, "/home/Jos\u{FFFD}/app");
+    }
+
+    #[test]
+    fn test_parse_rejects_binary() {
+        let path = std::env::temp_dir().join("linkerland-parser-binary.map");
+        std::fs::write(&path, b"\x7fELF\x02\x01\x01\x00\x00\xff\xfe").unwrap();
+        let result = parse(&path);
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(result, Err(Error::InvalidUtf8 { offset: 9, .. })));
+    }
 }
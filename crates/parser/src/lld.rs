@@ -0,0 +1,413 @@
+//! LLVM lld map files for ELF targets (`--Map=<file>`).
+//!
+//! mold prints the same table without the `LMA` column, so the table walker
+//! here is shared with [`crate::mold`].
+//!
+//! Every row starts with the same numeric columns and nesting is encoded by the
+//! indentation of the trailing text column: output sections follow the `Align`
+//! column directly, input sections (`file:(section)`) are indented by 8 and the
+//! symbols they define by 16.
+//!
+//! ```text
+//!              VMA              LMA     Size Align Out     In      Symbol
+//!           201000           201000      1a4    16 .text
+//!           201000           201000       40    16         main.o:(.text)
+//!           201000           201000        0     1                 main
+//! ```
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+use winnow::{
+    ascii::{digit1, space0, space1, till_line_ending},
+    combinator::preceded,
+    token::take_while,
+    Parser, Result as ParserResult,
+};
+
+use crate::{
+    invalid_row, is_non_alloc_section, kind::Classifier, next_line, BinaryFormat, InputSection,
+    MapDialect, MapFile, ObjectTable, Section, Symbol,
+};
+
+const HEADER: &str = "VMA LMA Size Align Out In Symbol";
+
+/// Construct named in errors for rows that do not parse.
+pub(crate) const ROW_LABEL: &str = "map row";
+
+/// Shape of the numeric columns in front of the `Out`/`In`/`Symbol` text.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Columns {
+    /// Whether an `LMA` column follows `VMA`
+    pub(crate) load_address: bool,
+    /// Radix of the address and size columns
+    pub(crate) radix: u32,
+    /// Dialect printing this layout
+    pub(crate) dialect: MapDialect,
+}
+
+pub(crate) const LLD_COLUMNS: Columns = Columns {
+    load_address: true,
+    radix: 16,
+    dialect: MapDialect::Lld,
+};
+
+/// Indentation of the text column for input sections and symbols.
+const INPUT_SECTION_INDENT: usize = 8;
+const SYMBOL_INDENT: usize = 16;
+
+/// Returns true if the first non-blank line matches `header` (ignoring spacing).
+pub(crate) fn sniff_header(head: &str, header: &str) -> bool {
+    head.lines()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| line.split_whitespace().eq(header.split_whitespace()))
+}
+
+/// Returns true if the map starts with lld's `VMA LMA Size Align Out In Symbol` header.
+pub(crate) fn sniff(head: &str) -> bool {
+    sniff_header(head, HEADER)
+}
+
+/// Parse an lld map into the common [`MapFile`] model.
+pub(crate) fn parse<'i>(input: &mut &'i str) -> ParserResult<MapFile<'i>> {
+    parse_table(input, LLD_COLUMNS)
+}
+
+/// One row of the table, with the text column's nesting depth.
+#[derive(Debug)]
+pub(crate) struct Row<'i> {
+    address: u64,
+    load_address: Option<u64>,
+    size: u64,
+    alignment: u64,
+    indent: usize,
+    text: &'i str,
+}
+
+/// Parse one numeric column in the given radix (e.g. `2002a8`); `0x` always means hex.
+fn number(radix: u32) -> impl FnMut(&mut &str) -> ParserResult<u64> {
+    move |input: &mut &str| {
+        preceded(space0, take_while(1.., |c: char| c.is_ascii_alphanumeric()))
+            .try_map(|digits: &str| match digits.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => u64::from_str_radix(digits, radix),
+            })
+            .parse_next(input)
+    }
+}
+
+/// Parse one row (e.g. `  201000  201000  40  16         main.o:(.text)`).
+fn row<'i>(input: &mut &'i str, columns: Columns) -> ParserResult<Row<'i>> {
+    let address = number(columns.radix).parse_next(input)?;
+    let load_address = if columns.load_address {
+        Some(number(columns.radix).parse_next(input)?)
+    } else {
+        None
+    };
+    let size = number(columns.radix).parse_next(input)?;
+    let alignment = preceded(space0, digit1.parse_to()).parse_next(input)?;
+    let padding = space1.parse_next(input)?;
+    let text = till_line_ending.parse_next(input)?;
+
+    Ok(Row {
+        address,
+        load_address,
+        size,
+        alignment,
+        // One space always separates `Align` from the text column.
+        indent: padding.len() - 1,
+        text: text.trim_end(),
+    })
+}
+
+/// Split an input section reference (e.g. `libfoo.a(foo.o):(.text.foo)`) into file and section.
+fn input_section_ref(text: &str) -> Option<(&str, &str)> {
+    let (file, section) = text.rsplit_once(":(")?;
+    Some((file, section.strip_suffix(')')?))
+}
+
+/// Walk a tabular lld/mold-style map with the given column layout.
+pub(crate) fn parse_table<'i>(input: &mut &'i str, columns: Columns) -> ParserResult<MapFile<'i>> {
+    let mut objects = ObjectTable::default();
+    let mut sections = Vec::new();
+    let mut symbols: Vec<Symbol> = Vec::new();
+    let mut current: Option<InputSection> = None;
+    let mut in_non_alloc = false;
+
+    // Header row
+    next_line(input);
+
+    loop {
+        let start = *input;
+        let Some(line) = next_line(input) else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut rest = line;
+        let Ok(row) = row(&mut rest, columns) else {
+            return Err(invalid_row(input, start, rest, ROW_LABEL));
+        };
+
+        if row.indent < INPUT_SECTION_INDENT {
+            if let Some(section) = current.take() {
+                section.push_symbols(&mut symbols);
+            }
+            in_non_alloc = is_non_alloc_section(row.text);
+            sections.push(Section {
+                address: row.address,
+                size: row.size,
+                segment: Cow::Borrowed(row.text),
+                section: Cow::Borrowed(row.text),
+                load_address: row.load_address.filter(|&lma| lma != row.address),
+                alignment: Some(row.alignment),
+                load_region: None,
+                execution_region: None,
+            });
+        } else if row.indent < SYMBOL_INDENT {
+            // Script commands (`. = ALIGN(0x10)`) share the input section column.
+            let Some((file, name)) = input_section_ref(row.text) else {
+                continue;
+            };
+            if let Some(section) = current.take() {
+                section.push_symbols(&mut symbols);
+            }
+            if in_non_alloc {
+                continue;
+            }
+            current = Some(InputSection {
+                name,
+                address: row.address,
+                size: row.size,
+                file_index: objects.intern(file),
+                symbols: Vec::new(),
+            });
+        } else if let Some(section) = current.as_mut() {
+            let size = (row.size > 0).then_some(row.size);
+            section.symbols.push((row.address, size, row.text));
+        }
+    }
+    if let Some(section) = current.take() {
+        section.push_symbols(&mut symbols);
+    }
+
+    Ok(MapFile {
+        arch: "unknown".into(),
+        object_files: objects.into_files(),
+        target_path: "".into(),
+        symbols,
+        dead_stripped: Vec::new(),
+        sections,
+        // Both linkers print this table for ELF outputs only.
+        binary_format: BinaryFormat::Elf,
+        memory_regions: Vec::new(),
+        dialect: columns.dialect,
+        invalid_utf8: 0,
+    })
+}
+
+/// The same walk as [`parse_table`], fed one line at a time by [`crate::SymbolStream`].
+///
+/// Only the rows of the input section being read are kept; they become symbols
+/// once the next input or output section starts. Sections and object files go
+/// into the stream's [`MapFile`] as soon as they are read, ahead of their symbols.
+pub(crate) struct TableRows {
+    columns: Columns,
+    objects: ObjectTable<'static>,
+    classifier: Classifier,
+    /// Rows of the current input section, starting with its own
+    pending: String,
+    in_non_alloc: bool,
+}
+
+impl TableRows {
+    pub(crate) fn new(columns: Columns) -> Self {
+        TableRows {
+            columns,
+            objects: ObjectTable::default(),
+            classifier: Classifier::default(),
+            pending: String::new(),
+            in_non_alloc: false,
+        }
+    }
+
+    /// Parser for one table row (not the column header) in this table's layout.
+    pub(crate) fn row_parser<'l>(&self) -> impl FnMut(&mut &'l str) -> ParserResult<Row<'l>> {
+        let columns = self.columns;
+        move |input: &mut &'l str| row(input, columns)
+    }
+
+    /// Add `row`, parsed from `line`, queueing the symbols of any input section it ends.
+    pub(crate) fn push_row(
+        &mut self,
+        line: &str,
+        row: Row<'_>,
+        out: &mut VecDeque<Symbol<'static>>,
+        map: &mut MapFile<'static>,
+    ) {
+        if row.indent < INPUT_SECTION_INDENT {
+            self.flush(out, map);
+            self.in_non_alloc = is_non_alloc_section(row.text);
+            let section = Section {
+                address: row.address,
+                size: row.size,
+                segment: Cow::Owned(row.text.to_string()),
+                section: Cow::Owned(row.text.to_string()),
+                load_address: row.load_address.filter(|&lma| lma != row.address),
+                alignment: Some(row.alignment),
+                load_region: None,
+                execution_region: None,
+            };
+            self.classifier.push_section(&section);
+            map.sections.push(section);
+        } else if row.indent < SYMBOL_INDENT {
+            // Script commands (`. = ALIGN(0x10)`) share the input section column.
+            if input_section_ref(row.text).is_none() {
+                return;
+            }
+            self.flush(out, map);
+            if !self.in_non_alloc {
+                self.pending.push_str(line);
+                self.pending.push('\n');
+            }
+        } else if !self.pending.is_empty() {
+            self.pending.push_str(line);
+            self.pending.push('\n');
+        }
+    }
+
+    /// Queue the symbols of the last input section.
+    pub(crate) fn finish(
+        &mut self,
+        out: &mut VecDeque<Symbol<'static>>,
+        map: &mut MapFile<'static>,
+    ) {
+        self.flush(out, map);
+    }
+
+    /// Turn the rows of the current input section into symbols, adding its
+    /// object file to `map` if it is new.
+    fn flush(&mut self, out: &mut VecDeque<Symbol<'static>>, map: &mut MapFile<'static>) {
+        let rows = std::mem::take(&mut self.pending);
+        let mut lines = rows.lines();
+        let Some(Ok(head)) = lines.next().map(|mut line| row(&mut line, self.columns)) else {
+            return;
+        };
+        let Some((file, name)) = input_section_ref(head.text) else {
+            return;
+        };
+        let known = self.objects.files.len();
+        let file_index = self.objects.intern(file.to_string());
+        if let Some(object) = self.objects.files.get(known) {
+            self.classifier.push_object(object);
+            map.object_files.push(object.clone());
+        }
+
+        let mut section = InputSection {
+            name,
+            address: head.address,
+            size: head.size,
+            file_index,
+            symbols: Vec::new(),
+        };
+        for mut line in lines {
+            if let Ok(row) = row(&mut line, self.columns) {
+                let size = (row.size > 0).then_some(row.size);
+                section.symbols.push((row.address, size, row.text));
+            }
+        }
+        let mut symbols = Vec::new();
+        section.push_symbols(&mut symbols);
+        out.extend(symbols.into_iter().map(|symbol| {
+            let mut symbol = symbol.into_owned();
+            symbol.kind = self.classifier.classify(&symbol);
+            symbol
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        assert!(sniff(
+            "             VMA              LMA     Size Align Out     In      Symbol\n"
+        ));
+        assert!(!sniff("Linker script and memory map\n"));
+    }
+
+    #[test]
+    fn test_row_depths() {
+        let mut input = "          201000           201000      1a4    16 .text";
+        let section = row(&mut input, LLD_COLUMNS).unwrap();
+        assert_eq!(section.address, 0x201000);
+        assert_eq!(section.load_address, Some(0x201000));
+        assert_eq!(section.size, 0x1a4);
+        assert_eq!(section.alignment, 16);
+        assert_eq!(section.indent, 0);
+        assert_eq!(section.text, ".text");
+
+        let mut input = "          201000           201000       40    16         main.o:(.text)";
+        assert_eq!(row(&mut input, LLD_COLUMNS).unwrap().indent, 8);
+
+        let mut input = "          201000           201000        0     1                 main";
+        let symbol = row(&mut input, LLD_COLUMNS).unwrap();
+        assert_eq!(symbol.indent, 16);
+        assert_eq!(symbol.text, "main");
+    }
+
+    #[test]
+    fn test_input_section_ref() {
+        assert_eq!(
+            input_section_ref("main.o:(.text)"),
+            Some(("main.o", ".text"))
+        );
+        assert_eq!(
+            input_section_ref("libfoo.a(foo.o):(.text._ZN3foo3barE)"),
+            Some(("libfoo.a(foo.o)", ".text._ZN3foo3barE"))
+        );
+        assert_eq!(input_section_ref(". = ALIGN(0x10)"), None);
+    }
+
+    #[test]
+    fn test_parse_table() {
+        let mut input = "             VMA              LMA     Size Align Out     In      Symbol
+          201000           201000       60    16 .text
+          201000           201000       40    16         main.o:(.text)
+          201000           201000       30     1                 main
+          201030           201030        0     1                 helper
+          201040           201040        4     1         . = ALIGN(0x10)
+          202000         08001000       10     8 .data
+          202000         08001000       10     8         main.o:(.data)
+";
+        let map = parse(&mut input).unwrap();
+        assert_eq!(map.sections.len(), 2);
+        assert_eq!(map.sections[0].alignment, Some(16));
+        assert_eq!(map.sections[0].load_address, None);
+        assert_eq!(map.sections[1].load_address, Some(0x8001000));
+        assert_eq!(map.object_files.len(), 1);
+        assert_eq!(map.symbols.len(), 3);
+        assert_eq!(map.symbols[0].name, "main");
+        assert_eq!(map.symbols[0].size, 0x30);
+        assert_eq!(map.symbols[1].name, "helper");
+        assert_eq!(map.symbols[1].size, 0x10);
+        assert_eq!(map.symbols[2].name, ".data");
+    }
+
+    #[test]
+    fn test_truncated_row_is_an_error() {
+        let contents = "             VMA              LMA     Size Align Out     In      Symbol
+          201000           201000       60    16 .text
+          201000           201000       40    16         main.o:(.text)
+          201000           201000       3";
+        let Err(crate::Error::Parse(error)) = crate::parse_str_as(contents, MapDialect::Lld) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(error.line, 4);
+        assert_eq!(error.label, Some(ROW_LABEL));
+        assert_eq!(error.snippet, "          201000           201000       3");
+    }
+}
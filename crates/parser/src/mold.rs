@@ -0,0 +1,66 @@
+//! mold map files (`-Map=<file>`).
+//!
+//! mold writes lld's table layout without the `LMA` column and with decimal
+//! numbers:
+//!
+//! ```text
+//!                VMA       Size Align Out     In      Symbol
+//!            2101248        308    16 .text
+//!            2101248         38    16         main.o:(.text)
+//!            2101248          0     0                 _start
+//! ```
+
+use winnow::Result as ParserResult;
+
+use crate::lld::{parse_table, sniff_header, Columns};
+use crate::{MapDialect, MapFile};
+
+const HEADER: &str = "VMA Size Align Out In Symbol";
+
+pub(crate) const MOLD_COLUMNS: Columns = Columns {
+    load_address: false,
+    radix: 10,
+    dialect: MapDialect::Mold,
+};
+
+/// Returns true if the map starts with mold's `VMA Size Align Out In Symbol` header.
+pub(crate) fn sniff(head: &str) -> bool {
+    sniff_header(head, HEADER)
+}
+
+/// Parse a mold map into the common [`MapFile`] model.
+pub(crate) fn parse<'i>(input: &mut &'i str) -> ParserResult<MapFile<'i>> {
+    parse_table(input, MOLD_COLUMNS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        assert!(sniff(
+            "               VMA       Size Align Out     In      Symbol\n"
+        ));
+        assert!(!sniff(
+            "             VMA              LMA     Size Align Out     In      Symbol\n"
+        ));
+    }
+
+    #[test]
+    fn test_parse_decimal_columns() {
+        let mut input = "               VMA       Size Align Out     In      Symbol
+           2101248         64    16 .text
+           2101248         64    16         main.o:(.text)
+           2101248          0     0                 main
+           2101280          0     0                 helper
+";
+        let map = parse(&mut input).unwrap();
+        assert_eq!(map.sections[0].address, 0x201000);
+        assert_eq!(map.sections[0].size, 0x40);
+        assert_eq!(map.sections[0].load_address, None);
+        assert_eq!(map.symbols.len(), 2);
+        assert_eq!(map.symbols[0].size, 0x20);
+        assert_eq!(map.symbols[1].address, 0x201020);
+    }
+}
@@ -0,0 +1,318 @@
+//! MSVC `link.exe` and LLVM `lld-link` map files (`/MAP`).
+//!
+//! The map opens with the module name, timestamp and preferred load address,
+//! followed by the section contribution table and the public symbols:
+//!
+//! ```text
+//!  Start         Length     Name                   Class
+//!  0001:00000000 000001a4H .text$mn                CODE
+//!
+//!   Address         Publics by Value              Rva+Base               Lib:Object
+//!
+//!  0001:00000000       main                       0000000140001000 f   main.obj
+//! ```
+//!
+//! Addresses are `section:offset` pairs; runtime addresses come from the
+//! `Rva+Base` column, and symbol sizes are inferred from the next symbol.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use winnow::{
+    ascii::{hex_digit1, space0, space1, till_line_ending},
+    combinator::{preceded, terminated},
+    token::take_till,
+    Parser, Result as ParserResult,
+};
+
+use crate::{
+    invalid_row, next_line, BinaryFormat, MapDialect, MapFile, ObjectFile, ObjectTable, Section,
+    Symbol, SymbolKind,
+};
+
+const PREFERRED_LOAD_ADDRESS: &str = "Preferred load address is";
+const SECTION_TABLE: &str = "Start Length Name Class";
+const PUBLICS: &str = "Address Publics by Value Rva+Base Lib:Object";
+const STATIC_SYMBOLS: &str = "Static symbols";
+
+/// Construct named in errors for `section:offset` rows that do not parse.
+const ROW_LABEL: &str = "section contribution or public symbol";
+
+/// Page size PE images align their sections to when no symbol pins a section's address.
+const SECTION_ALIGNMENT: u64 = 0x1000;
+
+/// Returns true if the head carries the `/MAP` preamble or section table.
+pub(crate) fn sniff(head: &str) -> bool {
+    head.lines().take(32).any(|line| {
+        line.trim_start().starts_with(PREFERRED_LOAD_ADDRESS)
+            || line.split_whitespace().eq(SECTION_TABLE.split_whitespace())
+    })
+}
+
+/// One row of the `Start Length Name Class` table.
+#[derive(Debug)]
+struct Contribution<'i> {
+    segment: u32,
+    offset: u64,
+    length: u64,
+    name: &'i str,
+}
+
+/// One row of the publics or static symbols table.
+#[derive(Debug)]
+struct Public<'i> {
+    segment: u32,
+    offset: u64,
+    name: &'i str,
+    address: u64,
+    object: &'i str,
+}
+
+/// Parse a `section:offset` address (e.g. `0001:000001a0`).
+fn segment_offset(input: &mut &str) -> ParserResult<(u32, u64)> {
+    (
+        preceded(space0, hex_digit1).try_map(|s| u32::from_str_radix(s, 16)),
+        preceded(':', hex_digit1).try_map(|s| u64::from_str_radix(s, 16)),
+    )
+        .parse_next(input)
+}
+
+/// Parse one contribution row (e.g. `0001:00000000 000001a4H .text$mn  CODE`).
+fn contribution<'i>(input: &mut &'i str) -> ParserResult<Contribution<'i>> {
+    let (segment, offset) = segment_offset(input)?;
+    let length = preceded(space1, terminated(hex_digit1, 'H'))
+        .try_map(|s| u64::from_str_radix(s, 16))
+        .parse_next(input)?;
+    let name = preceded(space1, take_till(1.., char::is_whitespace)).parse_next(input)?;
+    till_line_ending.parse_next(input)?;
+
+    Ok(Contribution {
+        segment,
+        offset,
+        length,
+        name,
+    })
+}
+
+/// Parse one symbol row (e.g. `0001:00000040  ?foo@@YAXXZ  0000000140001040 f i  foo.obj`).
+fn public<'i>(input: &mut &'i str) -> ParserResult<Public<'i>> {
+    let (segment, offset) = segment_offset(input)?;
+    let name = preceded(space1, take_till(1.., char::is_whitespace)).parse_next(input)?;
+    let address = preceded(space1, hex_digit1)
+        .try_map(|s| u64::from_str_radix(s, 16))
+        .parse_next(input)?;
+    let rest = till_line_ending.parse_next(input)?;
+
+    // Single-letter flags (`f` function, `i` inline) precede `lib:object`.
+    let mut rest = rest.trim();
+    while let Some((flag, tail)) = rest.split_once(char::is_whitespace) {
+        if flag.len() != 1 {
+            break;
+        }
+        rest = tail.trim_start();
+    }
+
+    Ok(Public {
+        segment,
+        offset,
+        name,
+        address,
+        object: rest,
+    })
+}
+
+/// PE section a COFF contribution is grouped into (`.text$mn` -> `.text`).
+fn section_group(name: &str) -> &str {
+    name.split_once('$').map_or(name, |(group, _)| group)
+}
+
+/// Parse a `/MAP` file into the common [`MapFile`] model.
+pub(crate) fn parse<'i>(input: &mut &'i str) -> ParserResult<MapFile<'i>> {
+    let mut target_path = "";
+    let mut preferred_base = 0;
+    let mut contributions = Vec::new();
+    let mut publics = Vec::new();
+
+    loop {
+        let start = *input;
+        let Some(line) = next_line(input) else {
+            break;
+        };
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if target_path.is_empty() {
+            target_path = trimmed;
+        } else if let Some(base) = trimmed.strip_prefix(PREFERRED_LOAD_ADDRESS) {
+            preferred_base = u64::from_str_radix(base.trim(), 16).unwrap_or(0);
+        } else if trimmed
+            .split_whitespace()
+            .eq(SECTION_TABLE.split_whitespace())
+            || trimmed.split_whitespace().eq(PUBLICS.split_whitespace())
+            || trimmed == STATIC_SYMBOLS
+        {
+            continue;
+        } else if let Ok(row) = contribution(&mut &*line) {
+            contributions.push(row);
+        } else if let Ok(row) = public(&mut &*line) {
+            publics.push(row);
+        } else if segment_offset(&mut &*line).is_ok() {
+            // Rows starting with `section:offset` belong to one of the tables.
+            return Err(invalid_row(input, start, line.trim_start(), ROW_LABEL));
+        }
+    }
+
+    // Runtime base of each PE section: pinned by any symbol inside it, otherwise
+    // laid out page-aligned after the previous section.
+    let mut segment_ends: BTreeMap<u32, u64> = BTreeMap::new();
+    for row in &contributions {
+        let end = segment_ends.entry(row.segment).or_default();
+        *end = (*end).max(row.offset.saturating_add(row.length));
+    }
+    let mut segment_bases: BTreeMap<u32, u64> = BTreeMap::new();
+    for row in publics.iter().filter(|row| row.segment != 0) {
+        segment_bases
+            .entry(row.segment)
+            .or_insert(row.address.saturating_sub(row.offset));
+    }
+    let mut next_base = preferred_base.saturating_add(SECTION_ALIGNMENT);
+    for (&segment, &length) in &segment_ends {
+        let base = *segment_bases.entry(segment).or_insert(next_base);
+        next_base = base
+            .saturating_add(length)
+            .checked_next_multiple_of(SECTION_ALIGNMENT)
+            .unwrap_or(u64::MAX);
+    }
+
+    let sections: Vec<Section> = contributions
+        .iter()
+        .map(|row| Section {
+            address: segment_bases[&row.segment].saturating_add(row.offset),
+            size: row.length,
+            segment: Cow::Borrowed(section_group(row.name)),
+            section: Cow::Borrowed(row.name),
+            load_address: None,
+            alignment: None,
+            load_region: None,
+            execution_region: None,
+        })
+        .collect();
+
+    let mut objects = ObjectTable::default();
+    let mut publics: Vec<_> = publics
+        .into_iter()
+        .filter(|row| row.segment != 0 && !row.object.is_empty())
+        .collect();
+    publics.sort_by_key(|row| (row.segment, row.offset));
+    publics.dedup_by_key(|row| (row.segment, row.offset, row.name));
+
+    let mut symbols = Vec::with_capacity(publics.len());
+    for (position, row) in publics.iter().enumerate() {
+        // A symbol extends to the next symbol or the end of its contribution.
+        let contribution_end = contributions
+            .iter()
+            .find(|c| {
+                c.segment == row.segment
+                    && c.offset <= row.offset
+                    && row.offset < c.offset.saturating_add(c.length)
+            })
+            .map_or(row.offset, |c| c.offset.saturating_add(c.length));
+        let end = publics
+            .get(position + 1)
+            .filter(|next| next.segment == row.segment)
+            .map_or(contribution_end, |next| next.offset.min(contribution_end));
+        symbols.push(Symbol {
+            address: row.address,
+            size: end.saturating_sub(row.offset),
+            file_index: objects.intern(row.object),
+            kind: SymbolKind::Data,
+            name: Cow::Borrowed(row.name),
+        });
+    }
+
+    Ok(MapFile {
+        arch: "unknown".into(),
+        object_files: objects
+            .into_files()
+            .into_iter()
+            .map(|file| ObjectFile::new_msvc(file.index, file.path))
+            .collect(),
+        target_path: Cow::Borrowed(target_path),
+        symbols,
+        dead_stripped: Vec::new(),
+        sections,
+        binary_format: BinaryFormat::Pe,
+        memory_regions: Vec::new(),
+        dialect: MapDialect::Msvc,
+        invalid_utf8: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        assert!(sniff(
+            " hello\n\n Timestamp is 65a1b2c3 (Fri Jan 12 10:00:00 2024)\n\n Preferred load address is 0000000140000000\n"
+        ));
+        assert!(!sniff("Linker script and memory map\n"));
+    }
+
+    #[test]
+    fn test_contribution() {
+        let mut input = " 0001:000001a0 00000030H .text$x                 CODE";
+        let row = contribution(&mut input).unwrap();
+        assert_eq!(row.segment, 1);
+        assert_eq!(row.offset, 0x1a0);
+        assert_eq!(row.length, 0x30);
+        assert_eq!(row.name, ".text$x");
+    }
+
+    #[test]
+    fn test_public_with_flags_and_library() {
+        let mut input =
+            " 0001:00000100       printf                     0000000140001100 f i libcmt:printf.obj";
+        let row = public(&mut input).unwrap();
+        assert_eq!(row.segment, 1);
+        assert_eq!(row.offset, 0x100);
+        assert_eq!(row.name, "printf");
+        assert_eq!(row.address, 0x140001100);
+        assert_eq!(row.object, "libcmt:printf.obj");
+    }
+
+    #[test]
+    fn test_section_group() {
+        assert_eq!(section_group(".text$mn"), ".text");
+        assert_eq!(section_group(".data"), ".data");
+    }
+
+    #[test]
+    fn test_bad_rows_are_errors() {
+        let contents = " app\n\n Preferred load address is 0000000140000000\n\n \
+                        Start         Length     Name                   Class\n \
+                        0001:00000000 000001a4H .text$mn                CODE\n \
+                        0001:000001a4 0000";
+        let Err(crate::Error::Parse(error)) = crate::parse_str_as(contents, MapDialect::Msvc)
+        else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(error.line, 7);
+        assert_eq!(error.label, Some(ROW_LABEL));
+        assert_eq!(error.snippet, " 0001:000001a4 0000");
+    }
+
+    #[test]
+    fn test_huge_offsets_saturate() {
+        let contents = " app\n\n Preferred load address is ffffffffffffffff\n\n \
+                        0001:ffffffffffffff00 ffffffffffffffffH .text$mn   CODE\n \
+                        0002:00000000 00000010H .data      DATA\n \
+                        0001:ffffffffffffff00  big  ffffffffffffffff f  big.obj\n";
+        let map = crate::parse_str_as(contents, MapDialect::Msvc).unwrap();
+        assert_eq!(map.sections[0].address, u64::MAX);
+        assert_eq!(map.sections[1].address, u64::MAX);
+        assert_eq!(map.symbols[0].size, 0xff);
+    }
+}
@@ -0,0 +1,194 @@
+//! Decomposition of object file paths into archive, member and [`ObjectKind`].
+//!
+//! Linkers name objects pulled out of static libraries after both: ld64 prints
+//! `libfoo.a[10](foo.o)`, GNU ld, gold, lld and armlink print `libfoo.a(foo.o)`
+//! and MSVC prints `libcmt:printf.obj`. A colon is also valid in a Unix file
+//! name, so the MSVC form is only split for MSVC maps (see [`library_object`]).
+
+use std::ops::Range;
+
+use serde::Serialize;
+
+/// Where an object file's contents come from.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    /// A standalone object file (e.g. `main.o`)
+    Object,
+    /// An object extracted from a static library (e.g. `libfoo.a(foo.o)`)
+    ArchiveMember,
+    /// A shared library or its text stub (e.g. `libSystem.tbd`, `libc.so.6`)
+    Dylib,
+    /// Content the linker generates itself (ld64 `linker synthesized`, lld `<internal>`)
+    LinkerSynthesized,
+    /// ld64's thread-local variable descriptors (`tlv-file`)
+    TlvFile,
+}
+
+impl ObjectKind {
+    /// Returns the human-readable name of the kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ObjectKind::Object => "object",
+            ObjectKind::ArchiveMember => "archive member",
+            ObjectKind::Dylib => "dylib",
+            ObjectKind::LinkerSynthesized => "linker synthesized",
+            ObjectKind::TlvFile => "tlv-file",
+        }
+    }
+}
+
+/// Object file paths linkers use for content they generate themselves.
+const SYNTHESIZED_OBJECTS: &[&str] = &[
+    "linker synthesized",
+    "<internal>",
+    "linker stubs",
+    "Linker created",
+    "- Linker created -",
+];
+
+/// Extensions of shared libraries and their link-time stubs.
+const DYLIB_EXTENSIONS: &[&str] = &[".tbd", ".dylib", ".so", ".dll"];
+
+/// The parts of an object file path, as byte ranges into it.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct PathParts {
+    pub(crate) archive: Option<Range<usize>>,
+    pub(crate) member: Option<Range<usize>>,
+    pub(crate) member_index: Option<u32>,
+    pub(crate) kind: ObjectKind,
+}
+
+/// Split `path` into its archive and member, and classify it.
+pub(crate) fn decompose(path: &str) -> PathParts {
+    let whole = |kind| PathParts {
+        archive: None,
+        member: None,
+        member_index: None,
+        kind,
+    };
+
+    if SYNTHESIZED_OBJECTS.contains(&path) {
+        return whole(ObjectKind::LinkerSynthesized);
+    }
+    if path == "tlv-file" {
+        return whole(ObjectKind::TlvFile);
+    }
+    if let Some(parts) = archive_member(path) {
+        return parts;
+    }
+    // Versioned ELF shared objects keep a suffix (`libc.so.6`).
+    let is_dylib = DYLIB_EXTENSIONS.iter().any(|ext| path.ends_with(ext)) || path.contains(".so.");
+    whole(if is_dylib {
+        ObjectKind::Dylib
+    } else {
+        ObjectKind::Object
+    })
+}
+
+/// `archive(member)` or ld64's `archive[index](member)`.
+fn archive_member(path: &str) -> Option<PathParts> {
+    let open = path.strip_suffix(')')?.rfind('(')?;
+    let member = open + 1..path.len() - 1;
+    let mut archive = 0..open;
+    let mut member_index = None;
+    if let Some(prefix) = path[..open].strip_suffix(']') {
+        let bracket = prefix.rfind('[')?;
+        member_index = Some(prefix[bracket + 1..].trim().parse().ok()?);
+        archive.end = bracket;
+    }
+    if archive.is_empty() || member.is_empty() {
+        return None;
+    }
+    Some(PathParts {
+        archive: Some(archive),
+        member: Some(member),
+        member_index,
+        kind: ObjectKind::ArchiveMember,
+    })
+}
+
+/// MSVC's `lib:object` (e.g. `libcmt:printf.obj`), as opposed to a drive letter.
+///
+/// Only applied to MSVC maps: elsewhere `foo:bar.o` is a plain file name.
+pub(crate) fn library_object(path: &str) -> Option<PathParts> {
+    let (library, object) = path.split_once(':')?;
+    if library.len() < 2 || object.is_empty() || library.contains(['/', '\\']) {
+        return None;
+    }
+    Some(PathParts {
+        archive: Some(0..library.len()),
+        member: Some(library.len() + 1..path.len()),
+        member_index: None,
+        kind: ObjectKind::ArchiveMember,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(path: &str) -> (Option<&str>, Option<&str>, Option<u32>, ObjectKind) {
+        let parts = decompose(path);
+        (
+            parts.archive.map(|r| &path[r]),
+            parts.member.map(|r| &path[r]),
+            parts.member_index,
+            parts.kind,
+        )
+    }
+
+    #[test]
+    fn test_ld64_archive_member() {
+        assert_eq!(
+            parts(
+                "target/out/libfoo.a[10](std-5f11fb1f9c11a5d1.std.86b73ce1986d3c1-cgu.06.rcgu.o)"
+            ),
+            (
+                Some("target/out/libfoo.a"),
+                Some("std-5f11fb1f9c11a5d1.std.86b73ce1986d3c1-cgu.06.rcgu.o"),
+                Some(10),
+                ObjectKind::ArchiveMember
+            )
+        );
+    }
+
+    #[test]
+    fn test_gnu_and_msvc_archive_members() {
+        assert_eq!(
+            parts("/usr/lib/libc.a(printf.o)"),
+            (
+                Some("/usr/lib/libc.a"),
+                Some("printf.o"),
+                None,
+                ObjectKind::ArchiveMember
+            )
+        );
+        let path = "libcmt:printf.obj";
+        let library = library_object(path).unwrap();
+        assert_eq!(library.archive.map(|r| &path[r]), Some("libcmt"));
+        assert_eq!(library.member.map(|r| &path[r]), Some("printf.obj"));
+        assert_eq!(library.kind, ObjectKind::ArchiveMember);
+        assert_eq!(library_object(r"C:\proj\main.obj"), None);
+    }
+
+    #[test]
+    fn test_colons_in_unix_file_names() {
+        for path in ["foo:bar.o", "build:1/x.o", "libcmt:printf.obj"] {
+            assert_eq!(
+                parts(path),
+                (None, None, None, ObjectKind::Object),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_kinds() {
+        assert_eq!(parts("linker synthesized").3, ObjectKind::LinkerSynthesized);
+        assert_eq!(parts("<internal>").3, ObjectKind::LinkerSynthesized);
+        assert_eq!(parts("tlv-file").3, ObjectKind::TlvFile);
+        assert_eq!(parts("/usr/lib/system/libunwind.tbd").3, ObjectKind::Dylib);
+        assert_eq!(parts("/lib/libc.so.6").3, ObjectKind::Dylib);
+        assert_eq!(parts("/target/debug/deps/app.o").3, ObjectKind::Object);
+    }
+}
@@ -0,0 +1,459 @@
+//! Streaming symbol reader for maps too large to hold in memory.
+//!
+//! ld64 maps list every symbol in a flat `# Symbols:` table, so once the header
+//! (path, arch, object files and sections) is parsed the table is read one line
+//! at a time and each row is yielded as soon as it is decoded; a trailing
+//! `# Dead Stripped Symbols:` table is collected into the header once the
+//! symbols are exhausted.
+//!
+//! lld and mold list output sections, input sections and symbols in one table,
+//! and size symbols from the input section around them, so the rows of one
+//! input section are kept until the next one starts; sections and object files
+//! are added to the header as they go by, each before its first symbol is
+//! yielded. The other dialects cannot be streamed and are rejected with
+//! [`Error::Unstreamable`].
+
+use std::collections::VecDeque;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+use winnow::{
+    error::{ContextError, StrContext},
+    stream::Offset,
+    Parser,
+};
+
+use crate::{
+    compression, kind::Classifier, ld64, lld, mold, parse_contents, unknown_dialect, BinaryFormat,
+    DeadSymbol, Error, MapDialect, MapFile, ParseError, Symbol, SNIFF_LEN,
+};
+
+/// Iterator over the symbols of a map, read incrementally.
+///
+/// [`SymbolStream::header`] holds everything but the symbols. For ld64 the
+/// path, arch, object files and sections are parsed up front, before the first
+/// symbol is yielded; lld and mold maps interleave them with the symbols, so
+/// the header grows as they are read, each section and object file arriving
+/// before the symbols placed in it. Iteration stops after the first error.
+pub struct SymbolStream<'r> {
+    map: MapFile<'static>,
+    rows: Rows<'r>,
+}
+
+enum Rows<'r> {
+    /// Rows of an ld64 `# Symbols:` table still to be read, classified by the header
+    Lines(Lines<'r>, Classifier),
+    /// Rows of an lld/mold table still to be read, and symbols decoded from them
+    Table(Lines<'r>, Box<lld::TableRows>, VecDeque<Symbol<'static>>),
+    /// Symbols left once the whole map has been read
+    Drained(std::collections::vec_deque::IntoIter<Symbol<'static>>),
+    Done,
+}
+
+impl<'r> SymbolStream<'r> {
+    /// Parse the header of the map in `reader`, detecting the dialect unless given.
+    pub(crate) fn new(
+        name: &Path,
+        reader: impl BufRead + 'r,
+        dialect: Option<MapDialect>,
+    ) -> Result<Self, Error> {
+        let mut lines = Lines::new(name, compression::reader(name, reader)?);
+
+        let mut head = String::new();
+        while head.len() < SNIFF_LEN {
+            let Some(line) = lines.next_line()? else {
+                break;
+            };
+            head.push_str(&line);
+            head.push('\n');
+        }
+        let Some(dialect) = dialect.or_else(|| MapDialect::detect(&head)) else {
+            return Err(unknown_dialect(name, &head));
+        };
+
+        let columns = match dialect {
+            MapDialect::Ld64 => None,
+            MapDialect::Lld => Some(lld::LLD_COLUMNS),
+            MapDialect::Mold => Some(mold::MOLD_COLUMNS),
+            _ => {
+                return Err(Error::Unstreamable {
+                    path: name.to_path_buf(),
+                    dialect,
+                })
+            }
+        };
+        if let Some(columns) = columns {
+            // Re-read the sniffed lines, past the column header.
+            lines.rewind(&head);
+            lines.next_row()?;
+            let map = MapFile {
+                arch: "unknown".into(),
+                object_files: Vec::new(),
+                target_path: "".into(),
+                symbols: Vec::new(),
+                dead_stripped: Vec::new(),
+                sections: Vec::new(),
+                binary_format: BinaryFormat::Elf,
+                memory_regions: Vec::new(),
+                dialect,
+                invalid_utf8: lines.invalid_utf8,
+            };
+            return Ok(SymbolStream {
+                map,
+                rows: Rows::Table(
+                    lines,
+                    Box::new(lld::TableRows::new(columns)),
+                    VecDeque::new(),
+                ),
+            });
+        }
+
+        // Re-read the sniffed lines, stopping after the `# Symbols:` table's column header.
+        lines.rewind(&head);
+        let mut header = String::new();
+        while let Some(line) = lines.next_line()? {
+            header.push_str(&line);
+            header.push('\n');
+            if line
+                .trim_start()
+                .starts_with(ld64::MapFileHeaders::Symbols.as_str())
+            {
+                if let Some(columns) = lines.next_line()? {
+                    header.push_str(&columns);
+                    header.push('\n');
+                }
+                break;
+            }
+        }
+        let mut map =
+            parse_contents(name, &header, Some(dialect), lines.invalid_utf8)?.into_owned();
+        map.symbols.clear();
+        let classifier = Classifier::new(&map);
+        Ok(SymbolStream {
+            map,
+            rows: Rows::Lines(lines, classifier),
+        })
+    }
+
+    /// The map's metadata; `symbols` is always empty.
+    ///
+    /// `invalid_utf8` counts the sequences replaced so far and `dead_stripped`
+    /// is only filled in once every symbol has been read. For lld and mold,
+    /// `sections` and `object_files` hold those read so far, which always
+    /// includes the section and object file of every symbol yielded.
+    pub fn header(&self) -> &MapFile<'static> {
+        &self.map
+    }
+
+    /// Consume the stream, returning the map's metadata.
+    pub fn into_header(self) -> MapFile<'static> {
+        self.map
+    }
+
+    /// Read the next ld64 symbol row, skipping blank lines.
+    ///
+    /// Rows of a trailing `# Dead Stripped Symbols:` table are read into `dead_stripped`.
+    fn next_row(
+        lines: &mut Lines<'_>,
+        classifier: &Classifier,
+        dead_stripped: &mut Vec<DeadSymbol<'static>>,
+    ) -> Result<Option<Symbol<'static>>, Error> {
+        let Some(line) = lines.next_row()? else {
+            return Ok(None);
+        };
+        // Trailing tables (e.g. `# Dead Stripped Symbols:`) end the symbol table.
+        let row = line.trim_start();
+        if row.starts_with(ld64::MapFileHeaders::DeadStrippedSymbols.as_str()) {
+            // Skip the column header.
+            lines.next_line()?;
+            while let Some(line) = lines.next_row()? {
+                if line.trim_start().starts_with("# ") {
+                    break;
+                }
+                let symbol = lines.parse_row(&line, "Dead Stripped Symbols", ld64::dead_symbol)?;
+                dead_stripped.push(symbol.into_owned());
+            }
+            return Ok(None);
+        }
+        if row.starts_with("# ") {
+            return Ok(None);
+        }
+        let mut symbol = lines
+            .parse_row(&line, "Symbols", ld64::symbol)?
+            .into_owned();
+        symbol.kind = classifier.classify(&symbol);
+        Ok(Some(symbol))
+    }
+}
+
+impl Iterator for SymbolStream<'_> {
+    type Item = Result<Symbol<'static>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = match &mut self.rows {
+            Rows::Lines(lines, classifier) => {
+                let row = Self::next_row(lines, classifier, &mut self.map.dead_stripped);
+                self.map.invalid_utf8 = lines.invalid_utf8;
+                row.transpose()
+            }
+            Rows::Table(lines, table, queue) => loop {
+                if let Some(symbol) = queue.pop_front() {
+                    break Some(Ok(symbol));
+                }
+                match lines.next_row() {
+                    Ok(Some(line)) => {
+                        match lines.parse_row(&line, lld::ROW_LABEL, table.row_parser()) {
+                            Ok(row) => table.push_row(&line, row, queue, &mut self.map),
+                            Err(error) => break Some(Err(error)),
+                        }
+                    }
+                    Ok(None) => {
+                        table.finish(queue, &mut self.map);
+                        let mut symbols = std::mem::take(queue).into_iter();
+                        let next = symbols.next().map(Ok);
+                        self.map.invalid_utf8 = lines.invalid_utf8;
+                        self.rows = Rows::Drained(symbols);
+                        return next;
+                    }
+                    Err(error) => break Some(Err(error)),
+                }
+                self.map.invalid_utf8 = lines.invalid_utf8;
+            },
+            Rows::Drained(symbols) => symbols.next().map(Ok),
+            Rows::Done => None,
+        };
+        if !matches!(next, Some(Ok(_))) {
+            self.rows = Rows::Done;
+        }
+        next
+    }
+}
+
+/// Decoded lines of a map, with the bookkeeping [`crate::decode`] does for whole files.
+struct Lines<'r> {
+    name: PathBuf,
+    reader: Box<dyn BufRead + 'r>,
+    /// Lines handed back by [`Lines::rewind`], served before the reader
+    pending: VecDeque<String>,
+    buf: Vec<u8>,
+    /// 1-based number of the last line returned
+    line: usize,
+    /// Bytes consumed from the reader
+    offset: usize,
+    invalid_utf8: usize,
+}
+
+impl<'r> Lines<'r> {
+    fn new(name: &Path, reader: Box<dyn BufRead + 'r>) -> Self {
+        Lines {
+            name: name.to_path_buf(),
+            reader,
+            pending: VecDeque::new(),
+            buf: Vec::new(),
+            line: 0,
+            offset: 0,
+            invalid_utf8: 0,
+        }
+    }
+
+    /// Serve the lines of `head` (as read so far) again, from line 1.
+    fn rewind(&mut self, head: &str) {
+        self.pending = head.lines().map(str::to_string).collect();
+        self.line = 0;
+    }
+
+    /// The next non-blank line, or `None` at the end of the map.
+    fn next_row(&mut self) -> Result<Option<String>, Error> {
+        while let Some(line) = self.next_line()? {
+            if !line.trim().is_empty() {
+                return Ok(Some(line));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parse `line`, the last one read, reporting failures at its line number.
+    fn parse_row<'l, O>(
+        &self,
+        line: &'l str,
+        label: &'static str,
+        mut parser: impl Parser<&'l str, O, ContextError>,
+    ) -> Result<O, Error> {
+        let mut input = line;
+        parser
+            .by_ref()
+            .context(StrContext::Label(label))
+            .parse_next(&mut input)
+            .map_err(|error| {
+                let offset = input.offset_from(&line);
+                let mut error = ParseError::new(&self.name, line, offset, &error);
+                error.line = self.line;
+                error.into()
+            })
+    }
+
+    /// The next line without its line ending, or `None` at the end of the map.
+    fn next_line(&mut self) -> Result<Option<String>, Error> {
+        if let Some(line) = self.pending.pop_front() {
+            self.line += 1;
+            return Ok(Some(line));
+        }
+
+        self.buf.clear();
+        let read = self
+            .reader
+            .read_until(b'\n', &mut self.buf)
+            .map_err(|e| Error::io(&self.name, e))?;
+        if read == 0 {
+            return Ok(None);
+        }
+        let offset = self.offset;
+        self.offset += read;
+        self.line += 1;
+
+        let bytes = self.buf.strip_suffix(b"\n").unwrap_or(&self.buf);
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        match std::str::from_utf8(bytes) {
+            Ok(line) => Ok(Some(line.to_string())),
+            // NUL bytes mean a binary rather than a map with Latin-1 paths.
+            Err(error) if bytes.contains(&0) => Err(Error::InvalidUtf8 {
+                path: self.name.clone(),
+                offset: offset + error.valid_up_to(),
+            }),
+            Err(_) => {
+                self.invalid_utf8 += bytes
+                    .utf8_chunks()
+                    .filter(|chunk| !chunk.invalid().is_empty())
+                    .count();
+                Ok(Some(String::from_utf8_lossy(bytes).into_owned()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::parse;
+
+    fn stream(path: &str) -> SymbolStream<'static> {
+        let file = std::fs::File::open(path).unwrap();
+        SymbolStream::new(Path::new(path), std::io::BufReader::new(file), None).unwrap()
+    }
+
+    #[test]
+    fn test_streams_ld64_symbols() {
+        let whole = parse(Path::new("tests/fixtures/linker.map")).unwrap();
+        let mut stream = stream("tests/fixtures/linker.map");
+        assert_eq!(stream.header().dialect, MapDialect::Ld64);
+        assert_eq!(stream.header().object_files.len(), whole.object_files.len());
+        assert_eq!(stream.header().sections.len(), whole.sections.len());
+        assert!(stream.header().symbols.is_empty());
+
+        let symbols: Vec<Symbol> = stream.by_ref().map(Result::unwrap).collect();
+        assert_eq!(symbols.len(), whole.symbols.len());
+        for (streamed, parsed) in symbols.iter().zip(&whole.symbols) {
+            assert_eq!(streamed.address, parsed.address);
+            assert_eq!(streamed.name, parsed.name);
+        }
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_streams_lld_tables() {
+        let whole = parse(Path::new("tests/fixtures/lld.map")).unwrap();
+        let mut stream = stream("tests/fixtures/lld.map");
+        assert_eq!(stream.header().dialect, MapDialect::Lld);
+        assert!(stream.header().sections.is_empty());
+
+        let mut symbols = Vec::new();
+        while let Some(symbol) = stream.next() {
+            let symbol = symbol.unwrap();
+            let header = stream.header();
+            assert!((symbol.file_index as usize) < header.object_files.len());
+            assert!(header.sections.iter().any(|section| {
+                (section.address..section.address + section.size).contains(&symbol.address)
+            }));
+            symbols.push(symbol);
+        }
+        assert_eq!(symbols, whole.symbols);
+        assert_eq!(stream.header().sections, whole.sections);
+        assert_eq!(stream.header().object_files, whole.object_files);
+        assert_eq!(stream.header().binary_format, whole.binary_format);
+    }
+
+    #[test]
+    fn test_rejects_other_dialects() {
+        let file = std::fs::File::open("tests/fixtures/gnu_ld.map").unwrap();
+        let reader = std::io::BufReader::new(file);
+        let Err(error) = SymbolStream::new(Path::new("gnu_ld.map"), reader, None) else {
+            panic!("expected GNU ld maps to be rejected");
+        };
+        assert!(matches!(
+            error,
+            Error::Unstreamable {
+                dialect: MapDialect::GnuLd,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_bad_row_reports_its_line() {
+        let map = "# Path: app\n# Arch: arm64\n# Object files:\n[  0] a.o\n# Sections:\n\
+                   # Address\tSize\tSegment\tSection\n# Symbols:\n# Address\tSize\tFile\tName\n\
+                   0x1000\t0x10\t[  0] _main\n0x1010\toops\n";
+        let mut stream = SymbolStream::new(Path::new("app.map"), map.as_bytes(), None).unwrap();
+        assert_eq!(stream.next().unwrap().unwrap().name, "_main");
+        let Some(Err(Error::Parse(error))) = stream.next() else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(error.line, 10);
+        assert_eq!(error.label, Some("Symbols"));
+        assert_eq!(error.snippet, "0x1010\toops");
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_bad_table_row_reports_its_line() {
+        let map = "             VMA              LMA     Size Align Out     In      Symbol\n\
+                   201000           201000       40    16 .text\n\
+                   201000           201000       40    16         main.o:(.text)\n\
+                   201000           201000        0     1                 main\n\
+                   201040           2010\n";
+        let mut stream = SymbolStream::new(Path::new("app.map"), map.as_bytes(), None).unwrap();
+        let Some(Err(Error::Parse(error))) = stream.next() else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(error.line, 5);
+        assert_eq!(error.label, Some(lld::ROW_LABEL));
+        assert_eq!(error.snippet, "201040           2010");
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_collects_dead_stripped_symbols() {
+        let map = "# Path: app\n# Arch: arm64\n# Object files:\n[  0] a.o\n# Sections:\n\
+                   # Address\tSize\tSegment\tSection\n# Symbols:\n# Address\tSize\tFile\tName\n\
+                   0x1000\t0x10\t[  0] _main\n\n# Dead Stripped Symbols:\n\
+                   #        \tSize    \tFile  Name\n<<dead>> \t0x00000018\t[  0] _unused\n";
+        let mut stream = SymbolStream::new(Path::new("app.map"), map.as_bytes(), None).unwrap();
+        assert_eq!(stream.next().unwrap().unwrap().name, "_main");
+        assert!(stream.header().dead_stripped.is_empty());
+        assert!(stream.next().is_none());
+        let dead = &stream.header().dead_stripped;
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].size, 0x18);
+        assert_eq!(dead[0].name, "_unused");
+    }
+
+    #[test]
+    fn test_counts_invalid_utf8_per_line() {
+        let map = b"# Path: /home/Jos\xe9/app\n# Arch: arm64\n# Object files:\n# Sections:\n\
+                    # Address\tSize\tSegment\tSection\n# Symbols:\n# Address\tSize\tFile\tName\n";
+        let stream = SymbolStream::new(Path::new("app.map"), &map[..], None).unwrap();
+        assert_eq!(stream.header().invalid_utf8, 1);
+        assert_eq!(stream.header().target_path, "/home/Jos\u{FFFD}/app");
+    }
+}
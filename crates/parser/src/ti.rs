@@ -0,0 +1,273 @@
+//! TI code generation tools map files (`--map_file`), e.g. the TI ARM linker.
+//!
+//! The `SECTION ALLOCATION MAP` lists output sections in column 0 (with a
+//! page column) and the input sections they contain, indented, as
+//! `origin length object (section)`. All numbers are hex without a prefix:
+//!
+//! ```text
+//! .text      0    0000026c    000009b2
+//!                   0000026c    00000110     main.obj (.text)
+//!                   0000037c    0000009c     rtsv7M4_T_le_eabi.lib : memcpy.asm.obj (.text)
+//! ```
+//!
+//! Load addresses of copied sections come from the `SEGMENT ALLOCATION MAP`,
+//! and symbols from the `GLOBAL SYMBOLS` tables.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use winnow::stream::Offset;
+use winnow::Result as ParserResult;
+
+use crate::{
+    attach_symbols, gnu, next_line, parse_hex, BinaryFormat, InputSection, MapDialect, MapFile,
+    MemoryRegion, ObjectTable, Section,
+};
+
+const OUTPUT_FILE_NAME: &str = "OUTPUT FILE NAME:";
+
+/// Returns true if the head carries the TI linker banner or output file line.
+pub(crate) fn sniff(head: &str) -> bool {
+    head.lines().map(str::trim).any(|line| {
+        line.starts_with(OUTPUT_FILE_NAME) || (line.starts_with("TI ") && line.contains("Linker"))
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    Other,
+    MemoryConfiguration,
+    SegmentAllocation,
+    SectionAllocation,
+    GlobalSymbols,
+}
+
+/// Architecture named by the banner (e.g. `TI ARM Linker PC v20.2.7`).
+fn arch_from_banner(line: &str) -> Option<String> {
+    let (target, _) = line.strip_prefix("TI ")?.split_once(" Linker")?;
+    Some(target.to_lowercase())
+}
+
+/// Parse a `MEMORY CONFIGURATION` row (e.g. `FLASH  00000000  00040000  00000c2e  0003f3d2  R  X`).
+fn memory_region(line: &str) -> Option<MemoryRegion<'_>> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let [name, origin, length, _used, _unused, attributes @ ..] = tokens.as_slice() else {
+        return None;
+    };
+    Some(MemoryRegion {
+        name: Cow::Borrowed(name),
+        origin: parse_hex(origin)?,
+        length: parse_hex(length)?,
+        attributes: Cow::Owned(attributes.concat()),
+    })
+}
+
+/// Parse a `SEGMENT ALLOCATION MAP` member row into its section name and run/load origins.
+fn segment_member(line: &str) -> Option<(&str, u64, u64)> {
+    if !line.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let [run, load, _length, _init_length, _attributes, member] = tokens.as_slice() else {
+        return None;
+    };
+    Some((member, parse_hex(run)?, parse_hex(load)?))
+}
+
+/// Split an input section's `object (section)` column, naming archive members `lib(member)`.
+///
+/// Holes (`--HOLE--`) and linker-generated tables (`(.cinit..data.load)`) have
+/// no object and are skipped.
+fn input_object(text: &str) -> Option<(Cow<'_, str>, &str)> {
+    let (object, section) = text.rsplit_once(" (")?;
+    let section = section.strip_suffix(')')?;
+    let object = match object.split_once(" : ") {
+        Some((library, member)) => Cow::Owned(format!("{}({})", library.trim(), member.trim())),
+        None => Cow::Borrowed(object.trim()),
+    };
+    Some((object, section))
+}
+
+/// Parse a `GLOBAL SYMBOLS` row (`0000026d  main`, with a leading page column on some targets).
+fn global_symbol(line: &str) -> Option<(u64, &str)> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let (address, name) = match tokens.as_slice() {
+        [address, name] => (address, name),
+        [page, address, name] if page.bytes().all(|b| b.is_ascii_digit()) => (address, name),
+        _ => return None,
+    };
+    Some((parse_hex(address)?, name))
+}
+
+/// Parse a TI linker map into the common [`MapFile`] model.
+pub(crate) fn parse<'i>(input: &mut &'i str) -> ParserResult<MapFile<'i>> {
+    let mut objects = ObjectTable::default();
+    let mut memory_regions = Vec::new();
+    let mut load_origins = HashMap::new();
+    let mut sections: Vec<Section> = Vec::new();
+    let mut input_sections = Vec::new();
+    let mut symbols = Vec::new();
+    let mut target_path = "";
+    let mut arch = Cow::Borrowed("unknown");
+    let mut block = Block::Other;
+    let mut wrapped_name: Option<&str> = None;
+
+    while let Some(line) = next_line(input) {
+        let trimmed = line.trim();
+        if let Some(banner) = arch_from_banner(trimmed) {
+            arch = Cow::Owned(banner);
+            continue;
+        }
+        if let Some(path) = trimmed.strip_prefix(OUTPUT_FILE_NAME) {
+            let path = path.trim();
+            target_path = path
+                .strip_prefix('<')
+                .and_then(|path| path.strip_suffix('>'))
+                .unwrap_or(path);
+            continue;
+        }
+        match trimmed {
+            "MEMORY CONFIGURATION" => block = Block::MemoryConfiguration,
+            "SEGMENT ALLOCATION MAP" => block = Block::SegmentAllocation,
+            "SECTION ALLOCATION MAP" => block = Block::SectionAllocation,
+            _ if trimmed.starts_with("GLOBAL SYMBOLS") => block = Block::GlobalSymbols,
+            _ if trimmed.ends_with("SUMMARY") || trimmed.starts_with("LINKER GENERATED") => {
+                block = Block::Other
+            }
+            _ => match block {
+                Block::MemoryConfiguration => memory_regions.extend(memory_region(line)),
+                Block::SegmentAllocation => {
+                    if let Some((member, run, load)) = segment_member(line) {
+                        if run != load {
+                            load_origins.insert(member, load);
+                        }
+                    }
+                }
+                Block::SectionAllocation => {
+                    let tokens: Vec<&str> = line.split_whitespace().collect();
+                    if let Some(name) = wrapped_name.take() {
+                        // Long names push the numbers onto the next line, marked `*`.
+                        let numbers = tokens.strip_prefix(&["*"]).unwrap_or(&tokens);
+                        if let [_page, origin, length, ..] = numbers {
+                            sections.extend(output_section(name, origin, length));
+                        }
+                    } else if !line.starts_with(char::is_whitespace) {
+                        match tokens.as_slice() {
+                            [name] => wrapped_name = Some(name),
+                            [name, _page, origin, length, ..] => {
+                                sections.extend(output_section(name, origin, length))
+                            }
+                            _ => {}
+                        }
+                    } else if let [origin, length, ..] = tokens.as_slice() {
+                        let (Some(address), Some(size)) = (parse_hex(origin), parse_hex(length))
+                        else {
+                            continue;
+                        };
+                        let end = length.offset_from(&line) + length.len();
+                        let text = line[end..].trim();
+                        let Some((object, name)) = input_object(text) else {
+                            continue;
+                        };
+                        input_sections.push(InputSection {
+                            name,
+                            address,
+                            size,
+                            file_index: objects.intern(object),
+                            symbols: Vec::new(),
+                        });
+                    }
+                }
+                Block::GlobalSymbols => symbols.extend(
+                    global_symbol(line).map(|(address, name)| (address, None::<u64>, name)),
+                ),
+                Block::Other => {}
+            },
+        }
+    }
+
+    for section in &mut sections {
+        section.load_address = load_origins.get(section.section.as_ref()).copied();
+    }
+    gnu::assign_regions(&mut sections, &memory_regions);
+
+    attach_symbols(&mut input_sections, symbols);
+    let mut map_symbols = Vec::new();
+    for mut section in input_sections {
+        // Thumb functions are listed with bit 0 set.
+        if arch == "arm" && section.name.starts_with(".text") {
+            for symbol in &mut section.symbols {
+                symbol.0 &= !1;
+            }
+        }
+        section.push_symbols(&mut map_symbols);
+    }
+
+    Ok(MapFile {
+        arch,
+        object_files: objects.into_files(),
+        target_path: Cow::Borrowed(target_path),
+        symbols: map_symbols,
+        dead_stripped: Vec::new(),
+        sections,
+        binary_format: BinaryFormat::Elf,
+        memory_regions,
+        dialect: MapDialect::Ti,
+        invalid_utf8: 0,
+    })
+}
+
+/// Build an output section from its `SECTION ALLOCATION MAP` columns.
+fn output_section<'i>(name: &'i str, origin: &str, length: &str) -> Option<Section<'i>> {
+    Some(Section {
+        address: parse_hex(origin)?,
+        size: parse_hex(length)?,
+        segment: Cow::Borrowed(name),
+        section: Cow::Borrowed(name),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        assert!(sniff(
+            "*****\n                  TI ARM Linker PC v20.2.7                     \n*****\n"
+        ));
+        assert!(!sniff("Linker script and memory map\n"));
+    }
+
+    #[test]
+    fn test_memory_region() {
+        let region =
+            memory_region("  SRAM                  20000000   00008000  00000218  00007de8  RW X")
+                .unwrap();
+        assert_eq!(region.name, "SRAM");
+        assert_eq!(region.origin, 0x20000000);
+        assert_eq!(region.length, 0x8000);
+        assert_eq!(region.attributes, "RWX");
+    }
+
+    #[test]
+    fn test_input_object() {
+        assert_eq!(
+            input_object("main.obj (.text)"),
+            Some(("main.obj".into(), ".text"))
+        );
+        assert_eq!(
+            input_object("rtsv7M4_T_le_eabi.lib : memcpy_t2.asm.obj (.text)"),
+            Some(("rtsv7M4_T_le_eabi.lib(memcpy_t2.asm.obj)".into(), ".text"))
+        );
+        assert_eq!(input_object("--HOLE-- [fill = 0]"), None);
+    }
+
+    #[test]
+    fn test_global_symbol() {
+        assert_eq!(global_symbol("0000026d  main"), Some((0x26d, "main")));
+        assert_eq!(global_symbol("0     0000026d  main"), Some((0x26d, "main")));
+        assert_eq!(global_symbol("-------   ----"), None);
+    }
+}
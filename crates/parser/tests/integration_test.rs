@@ -1,4 +1,8 @@
-use linkerland_parser::{parse, BinaryFormat};
+use linkerland_parser::{
+    load, load_reader, parse, parse_as, parse_reader, parse_str, parse_str_as, stream,
+    stream_reader, BinaryFormat, Error, MapDialect, ObjectKind, SymbolKind,
+};
+use std::borrow::Cow;
 use std::path::Path;
 
 #[test]
@@ -18,3 +22,563 @@ fn test_parse_linker_map_detects_macho() {
     assert!(map.sections.iter().any(|s| s.segment.starts_with("__")));
     assert!(!map.symbols.is_empty());
 }
+
+#[test]
+fn test_parse_linker_map_headers_independent_of_host() {
+    let map = parse(Path::new("tests/fixtures/linker.map")).unwrap();
+    assert_eq!(
+        map.target_path,
+        "/target/debug/deps/learning_linkers-97732971bdfee10d"
+    );
+    assert_eq!(map.object_files.len(), 67);
+    assert_eq!(map.object_files[0].path, "linker synthesized");
+    assert_eq!(map.sections.len(), 14);
+    assert_eq!(map.sections[0].segment, "__TEXT");
+    assert_eq!(map.sections[0].section, "__text");
+}
+
+#[test]
+fn test_parse_gnu_ld_map() {
+    let map = parse(Path::new("tests/fixtures/gnu_ld.map")).unwrap();
+    assert_eq!(map.binary_format, BinaryFormat::Elf);
+    assert_eq!(map.target_path, "target/release/hello");
+    assert_eq!(map.arch, "x86_64");
+    assert!(map.memory_regions.is_empty());
+
+    let sections: Vec<_> = map.sections.iter().map(|s| &*s.section).collect();
+    assert_eq!(
+        sections,
+        [
+            ".interp",
+            ".rela.dyn",
+            ".init",
+            ".text",
+            ".rodata",
+            ".data",
+            ".bss",
+            ".comment",
+            ".debug_info"
+        ]
+    );
+
+    let paths: Vec<_> = map.object_files.iter().map(|o| &*o.path).collect();
+    assert!(paths.contains(&"main.o"));
+    assert!(paths.contains(&"/usr/lib/x86_64-linux-gnu/libc.a(printf.o)"));
+    assert!(!paths.iter().any(|p| p.ends_with(".a")));
+
+    let main = map.symbols.iter().find(|s| s.name == "main").unwrap();
+    assert_eq!(main.address, 0x401070);
+    assert_eq!(main.size, 0x20);
+    let main_o = map
+        .object_files
+        .iter()
+        .find(|o| o.path == "main.o")
+        .unwrap();
+    assert_eq!(main.file_index, main_o.index);
+
+    // Aliases share an address; the last one listed owns the bytes.
+    let printf = map.symbols.iter().find(|s| s.name == "printf").unwrap();
+    assert_eq!(printf.size, 0xC0);
+
+    // Input sections without symbols are attributed by their section name.
+    assert!(map
+        .symbols
+        .iter()
+        .any(|s| s.name == ".rodata.str1.1" && s.size == 0x3C));
+    assert!(!map.symbols.iter().any(|s| s.name.contains('=')));
+    assert!(!map.symbols.iter().any(|s| s.name.starts_with(".debug")));
+}
+
+#[test]
+fn test_parse_lld_map() {
+    let map = parse(Path::new("tests/fixtures/lld.map")).unwrap();
+    assert_eq!(map.binary_format, BinaryFormat::Elf);
+    assert_eq!(map.sections.len(), 8);
+
+    let text = map.sections.iter().find(|s| s.section == ".text").unwrap();
+    assert_eq!(text.address, 0x201000);
+    assert_eq!(text.size, 0x134);
+    assert_eq!(text.alignment, Some(16));
+    assert_eq!(text.load_address, None);
+
+    let paths: Vec<_> = map.object_files.iter().map(|o| &*o.path).collect();
+    assert!(paths.contains(&"<internal>"));
+    assert!(paths.contains(&"/usr/lib/x86_64-linux-gnu/libc.a(printf.o)"));
+
+    let printf = map.symbols.iter().find(|s| s.name == "printf").unwrap();
+    assert_eq!(printf.size, 0x94);
+    let main = map.symbols.iter().find(|s| s.name == "main").unwrap();
+    assert_eq!(main.address, 0x201080);
+    assert_eq!(main.size, 0x20);
+    assert!(!map.symbols.iter().any(|s| s.name.starts_with(".debug")));
+}
+
+#[test]
+fn test_parse_mold_map() {
+    let map = parse(Path::new("tests/fixtures/mold.map")).unwrap();
+    assert_eq!(map.binary_format, BinaryFormat::Elf);
+    assert_eq!(map.sections.len(), 6);
+    let text = map.sections.iter().find(|s| s.section == ".text").unwrap();
+    assert_eq!(text.address, 0x201000);
+    assert_eq!(text.size, 0x134);
+    assert_eq!(text.alignment, Some(16));
+    let printf = map.symbols.iter().find(|s| s.name == "printf").unwrap();
+    assert_eq!(printf.size, 0x94);
+}
+
+#[test]
+fn test_parse_gold_map() {
+    let map = parse(Path::new("tests/fixtures/gold.map")).unwrap();
+    assert_eq!(map.binary_format, BinaryFormat::Elf);
+    assert_eq!(map.sections.len(), 6);
+    let paths: Vec<_> = map.object_files.iter().map(|o| &*o.path).collect();
+    assert_eq!(paths[0], "/usr/lib/x86_64-linux-gnu/libc.a(printf.o)");
+    let main = map.symbols.iter().find(|s| s.name == "main").unwrap();
+    assert_eq!(main.address, 0x401080);
+    assert_eq!(main.size, 0x20);
+    assert!(!map.symbols.iter().any(|s| s.name.starts_with("0x")));
+}
+
+#[test]
+fn test_elf_dialects_agree_on_symbol_sizes() {
+    let maps: Vec<_> = ["lld.map", "mold.map", "gold.map"]
+        .iter()
+        .map(|name| parse(&Path::new("tests/fixtures").join(name)).unwrap())
+        .collect();
+    for name in ["main", "printf", "GREETING", "COUNTER", "_IO_stdin_used"] {
+        let sizes: Vec<_> = maps
+            .iter()
+            .map(|map| &map.symbols.iter().find(|s| s.name == name).unwrap().size)
+            .collect();
+        assert!(sizes.windows(2).all(|w| w[0] == w[1]), "{name}: {sizes:?}");
+    }
+}
+
+#[test]
+fn test_parse_msvc_map() {
+    let map = parse(Path::new("tests/fixtures/msvc.map")).unwrap();
+    assert_eq!(map.binary_format, BinaryFormat::Pe);
+    assert_eq!(map.target_path, "hello");
+    assert_eq!(map.sections.len(), 7);
+
+    let text = &map.sections[0];
+    assert_eq!(text.segment, ".text");
+    assert_eq!(text.section, ".text$mn");
+    assert_eq!(text.address, 0x140001000);
+    assert_eq!(text.size, 0x120);
+    let bss = map.sections.iter().find(|s| s.section == ".bss").unwrap();
+    assert_eq!(bss.address, 0x140004018);
+
+    let paths: Vec<_> = map.object_files.iter().map(|o| &*o.path).collect();
+    assert!(paths.contains(&"main.obj"));
+    assert!(paths.contains(&"libcmt:printf.obj"));
+    assert!(!paths.contains(&"<absolute>"));
+
+    let greet = map
+        .symbols
+        .iter()
+        .find(|s| s.name == "?greet@@YAXPEBD@Z")
+        .unwrap();
+    assert_eq!(greet.address, 0x140001040);
+    assert_eq!(greet.size, 0x60);
+    // The last symbol in a contribution runs to the contribution's end.
+    let printf = map.symbols.iter().find(|s| s.name == "printf").unwrap();
+    assert_eq!(printf.size, 0x80);
+    let counter = map.symbols.iter().find(|s| s.name == "counter").unwrap();
+    assert_eq!(counter.size, 0x10);
+    assert!(map.symbols.iter().any(|s| s.name == "$LN5"));
+}
+
+#[test]
+fn test_parse_gcc_arm_map_assigns_regions() {
+    let map = parse(Path::new("tests/fixtures/gcc_arm.map")).unwrap();
+    assert_eq!(map.arch, "arm");
+    assert_eq!(map.target_path, "build/firmware.elf");
+    let regions: Vec<_> = map.memory_regions.iter().map(|r| &*r.name).collect();
+    assert_eq!(regions, ["FLASH", "RAM"]);
+
+    let text = map.sections.iter().find(|s| s.section == ".text").unwrap();
+    assert_eq!(text.execution_region.as_deref(), Some("FLASH"));
+    assert_eq!(text.load_region.as_deref(), Some("FLASH"));
+    let data = map.sections.iter().find(|s| s.section == ".data").unwrap();
+    assert_eq!(data.load_address, Some(0x00000000080000cc));
+    assert_eq!(data.execution_region.as_deref(), Some("RAM"));
+    assert_eq!(data.load_region.as_deref(), Some("FLASH"));
+    let bss = map.sections.iter().find(|s| s.section == ".bss").unwrap();
+    assert_eq!(bss.load_region.as_deref(), Some("RAM"));
+    let attributes = map
+        .sections
+        .iter()
+        .find(|s| s.section == ".ARM.attributes")
+        .unwrap();
+    assert_eq!(attributes.execution_region, None);
+}
+
+#[test]
+fn test_parse_armlink_map() {
+    let map = parse(Path::new("tests/fixtures/armlink.map")).unwrap();
+    assert_eq!(map.arch, "arm");
+    assert_eq!(map.binary_format, BinaryFormat::Elf);
+    assert_eq!(map.sections.len(), 9);
+
+    let data = map.sections.iter().find(|s| s.section == ".data").unwrap();
+    assert_eq!(data.segment, "RW_IRAM1");
+    assert_eq!(data.address, 0x20000000);
+    assert_eq!(data.load_address, Some(0x80000D8));
+    assert_eq!(data.load_region.as_deref(), Some("LR_IROM1"));
+    assert_eq!(data.execution_region.as_deref(), Some("RW_IRAM1"));
+    let text = map
+        .sections
+        .iter()
+        .find(|s| s.section == ".text.main")
+        .unwrap();
+    assert_eq!(text.load_address, None);
+    assert_eq!(text.execution_region.as_deref(), Some("ER_IROM1"));
+
+    let paths: Vec<_> = map.object_files.iter().map(|o| &*o.path).collect();
+    assert!(paths.contains(&"mc_w.l(entry.o)"));
+
+    let main = map.symbols.iter().find(|s| s.name == "main").unwrap();
+    assert_eq!(main.address, 0x8000064);
+    assert_eq!(main.size, 0x28);
+    let reset = map
+        .symbols
+        .iter()
+        .find(|s| s.name == "Reset_Handler")
+        .unwrap();
+    assert_eq!(reset.address, 0x8000040);
+    assert_eq!(reset.size, 0x8);
+    let rx_buffer = map.symbols.iter().find(|s| s.name == "rx_buffer").unwrap();
+    assert_eq!(rx_buffer.size, 0xC);
+    assert!(!map.symbols.iter().any(|s| s.name == "../Source/main.c"));
+}
+
+#[test]
+fn test_parse_iar_map() {
+    let map = parse(Path::new("tests/fixtures/iar.map")).unwrap();
+    assert_eq!(map.arch, "arm");
+    assert_eq!(map.target_path, "C:\\proj\\Debug\\Exe\\app.out");
+    assert_eq!(map.binary_format, BinaryFormat::Elf);
+
+    let sections: Vec<_> = map.sections.iter().map(|s| &*s.section).collect();
+    assert_eq!(
+        sections,
+        [
+            ".intvec",
+            ".text",
+            ".text",
+            ".text",
+            ".rodata",
+            "Initializer bytes",
+            ".iar.init_table",
+            ".data",
+            ".bss",
+            "CSTACK"
+        ]
+    );
+    let bss = map.sections.iter().find(|s| s.section == ".bss").unwrap();
+    assert_eq!(bss.segment, "P2");
+    assert_eq!(bss.address, 0x20000004);
+
+    let paths: Vec<_> = map.object_files.iter().map(|o| &*o.path).collect();
+    assert!(paths.contains(&"C:\\proj\\Debug\\Obj\\main.o"));
+    assert!(paths.contains(&"dl7M_tln.a(exit.o)"));
+    assert!(paths.contains(&"rt7M_tl.a(vector_table_M.o)"));
+
+    let main = map.symbols.iter().find(|s| s.name == "main").unwrap();
+    assert_eq!(main.address, 0x8000040);
+    assert_eq!(main.size, 0x28);
+    let start = map
+        .symbols
+        .iter()
+        .find(|s| s.name == "__iar_program_start_with_long_name")
+        .unwrap();
+    assert_eq!(start.address, 0x80000AC);
+    assert!(map.symbols.iter().any(|s| s.name == "rx_count"));
+    assert!(!map.symbols.iter().any(|s| s.name.contains("$$")));
+}
+
+#[test]
+fn test_parse_ti_map() {
+    let map = parse(Path::new("tests/fixtures/ti.map")).unwrap();
+    assert_eq!(map.arch, "arm");
+    assert_eq!(map.target_path, "blinky.out");
+    assert_eq!(map.binary_format, BinaryFormat::Elf);
+    assert_eq!(map.memory_regions.len(), 2);
+    assert_eq!(map.memory_regions[1].origin, 0x20000000);
+
+    let sections: Vec<_> = map.sections.iter().map(|s| &*s.section).collect();
+    assert_eq!(
+        sections,
+        [
+            ".intvecs",
+            ".text",
+            ".const",
+            ".cinit",
+            ".init_array",
+            ".data",
+            ".bss",
+            ".stack"
+        ]
+    );
+    let data = map.sections.iter().find(|s| s.section == ".data").unwrap();
+    assert_eq!(data.load_address, Some(0x330));
+    assert_eq!(data.execution_region.as_deref(), Some("SRAM"));
+    assert_eq!(data.load_region.as_deref(), Some("FLASH"));
+
+    let paths: Vec<_> = map.object_files.iter().map(|o| &*o.path).collect();
+    assert!(paths.contains(&"rtsv7M4_T_le_v4SPD16_eabi.lib(boot_cortex_m.c.obj)"));
+    assert!(!paths.iter().any(|p| p.contains("HOLE")));
+
+    let main = map.symbols.iter().find(|s| s.name == "main").unwrap();
+    assert_eq!(main.address, 0x26C);
+    assert_eq!(main.size, 0x58);
+    let greeting = map.symbols.iter().find(|s| s.name == "GREETING").unwrap();
+    assert_eq!(greeting.size, 0x10);
+    assert_eq!(map.symbols.iter().filter(|s| s.name == "main").count(), 1);
+    assert!(!map.symbols.iter().any(|s| s.name == "__STACK_SIZE"));
+}
+
+#[test]
+fn test_fixtures_report_dialect() {
+    let fixtures = [
+        ("linker.map", MapDialect::Ld64),
+        ("gnu_ld.map", MapDialect::GnuLd),
+        ("gcc_arm.map", MapDialect::GnuLd),
+        ("gold.map", MapDialect::Gold),
+        ("lld.map", MapDialect::Lld),
+        ("mold.map", MapDialect::Mold),
+        ("msvc.map", MapDialect::Msvc),
+        ("armlink.map", MapDialect::Armlink),
+        ("iar.map", MapDialect::Iar),
+        ("ti.map", MapDialect::Ti),
+    ];
+    for (fixture, dialect) in fixtures {
+        let map = parse(&Path::new("tests/fixtures").join(fixture)).unwrap();
+        assert_eq!(map.dialect, dialect, "{fixture}");
+    }
+}
+
+#[test]
+fn test_parse_as_overrides_detection() {
+    let map = parse_as(Path::new("tests/fixtures/gold.map"), MapDialect::GnuLd).unwrap();
+    assert_eq!(map.dialect, MapDialect::GnuLd);
+    assert!(!map.symbols.is_empty());
+}
+
+#[test]
+fn test_parse_str_and_reader_match_path() {
+    let path = Path::new("tests/fixtures/mold.map");
+    let contents = std::fs::read_to_string(path).unwrap();
+    let from_path = parse(path).unwrap();
+    let from_str = parse_str(&contents).unwrap();
+    let from_reader = parse_reader(contents.as_bytes()).unwrap();
+    for map in [&from_str, &from_reader] {
+        assert_eq!(map.dialect, from_path.dialect);
+        assert_eq!(map.sections.len(), from_path.sections.len());
+        assert_eq!(map.symbols.len(), from_path.symbols.len());
+    }
+
+    let map = parse_str_as(&contents, MapDialect::Mold).unwrap();
+    assert_eq!(map.symbols.len(), from_path.symbols.len());
+}
+
+#[test]
+fn test_parse_reader_reports_input_name() {
+    let Err(Error::Parse(error)) = parse_reader("not a linker map\n".as_bytes()) else {
+        panic!("expected a parse error");
+    };
+    assert_eq!(error.path, Path::new("<input>"));
+    assert_eq!(error.snippet, "not a linker map");
+}
+
+#[cfg(all(feature = "gzip", feature = "zstd", feature = "xz"))]
+#[test]
+fn test_parse_compressed_maps() {
+    for (compressed, plain) in [
+        ("lld.map.gz", "lld.map"),
+        ("mold.map.zst", "mold.map"),
+        ("gold.map.xz", "gold.map"),
+    ] {
+        let fixtures = Path::new("tests/fixtures");
+        let map = parse(&fixtures.join(compressed)).unwrap();
+        let expected = parse(&fixtures.join(plain)).unwrap();
+        assert_eq!(map.dialect, expected.dialect, "{compressed}");
+        assert_eq!(map.symbols.len(), expected.symbols.len(), "{compressed}");
+    }
+}
+
+#[test]
+fn test_stream_matches_parse() {
+    for fixture in [
+        "linker.map",
+        "dead_strip.map",
+        "lld.map",
+        "lld.map.gz",
+        "mold.map",
+        "mold.map.zst",
+    ] {
+        if fixture.ends_with(".gz") && !cfg!(feature = "gzip")
+            || fixture.ends_with(".zst") && !cfg!(feature = "zstd")
+        {
+            continue;
+        }
+        let path = Path::new("tests/fixtures").join(fixture);
+        let map = parse(&path).unwrap();
+        let mut symbols = stream(&path).unwrap();
+        assert_eq!(symbols.header().dialect, map.dialect, "{fixture}");
+        let names: Vec<(SymbolKind, String)> = symbols
+            .by_ref()
+            .map(|s| s.unwrap())
+            .map(|s| (s.kind, s.name.into_owned()))
+            .collect();
+        let expected: Vec<(SymbolKind, String)> = map
+            .symbols
+            .iter()
+            .map(|s| (s.kind, s.name.to_string()))
+            .collect();
+        assert_eq!(names, expected, "{fixture}");
+        assert_eq!(symbols.header().sections, map.sections, "{fixture}");
+        assert_eq!(symbols.header().object_files, map.object_files, "{fixture}");
+        assert_eq!(
+            symbols.header().dead_stripped,
+            map.dead_stripped,
+            "{fixture}"
+        );
+    }
+
+    for fixture in [
+        "gnu_ld.map",
+        "gold.map",
+        "msvc.map",
+        "armlink.map",
+        "iar.map",
+        "ti.map",
+    ] {
+        let path = Path::new("tests/fixtures").join(fixture);
+        assert!(
+            matches!(stream(&path), Err(Error::Unstreamable { .. })),
+            "{fixture}"
+        );
+    }
+
+    let contents = std::fs::read("tests/fixtures/linker.map").unwrap();
+    let symbols = stream_reader(&contents[..]).unwrap();
+    assert_eq!(
+        symbols.count(),
+        parse(Path::new("tests/fixtures/linker.map"))
+            .unwrap()
+            .symbols
+            .len()
+    );
+}
+
+#[test]
+fn test_parse_ld64_dead_stripped_symbols() {
+    let map = parse(Path::new("tests/fixtures/dead_strip.map")).unwrap();
+    assert_eq!(map.symbols.len(), 2);
+    assert_eq!(map.dead_stripped.len(), 3);
+
+    let dead = &map.dead_stripped[1];
+    assert_eq!(dead.size, 0x40);
+    assert_eq!(dead.file_index, 2);
+    assert_eq!(dead.name, "__ZN3app6unused5parse17hfedcba9876543210E");
+    assert_eq!(map.dead_stripped[2].name, "literal string: debug");
+}
+
+#[test]
+fn test_ld64_symbol_kinds() {
+    let map = parse(Path::new("tests/fixtures/linker.map")).unwrap();
+    let count = |kind| map.symbols.iter().filter(|s| s.kind == kind).count();
+    assert_eq!(count(SymbolKind::Fde), 535);
+    assert_eq!(count(SymbolKind::ExceptionTable), 169);
+    assert_eq!(count(SymbolKind::Stub), 64);
+
+    let kind_of = |name: &str| map.symbols.iter().find(|s| s.name == name).unwrap().kind;
+    assert_eq!(kind_of("___error.stub"), SymbolKind::Stub);
+    assert_eq!(kind_of("ltmp1"), SymbolKind::Anonymous);
+    assert_eq!(
+        kind_of("__ZN4core3ops8function6FnOnce9call_once17h83d0cee0de1dbabeE"),
+        SymbolKind::Function
+    );
+
+    let map = parse(Path::new("tests/fixtures/lld.map")).unwrap();
+    assert!(map
+        .symbols
+        .iter()
+        .any(|s| s.kind == SymbolKind::LinkerSynthesized));
+}
+
+#[test]
+fn test_archive_members_are_decomposed() {
+    let map = parse(Path::new("tests/fixtures/gnu_ld.map")).unwrap();
+    let printf = map
+        .object_files
+        .iter()
+        .find(|o| o.path == "/usr/lib/x86_64-linux-gnu/libc.a(printf.o)")
+        .unwrap();
+    assert_eq!(printf.kind, ObjectKind::ArchiveMember);
+    assert_eq!(
+        printf.archive.as_deref(),
+        Some("/usr/lib/x86_64-linux-gnu/libc.a")
+    );
+    assert_eq!(printf.member.as_deref(), Some("printf.o"));
+    assert_eq!(printf.display_name(), "printf.o");
+
+    let map = parse(Path::new("tests/fixtures/msvc.map")).unwrap();
+    let printf = map
+        .object_files
+        .iter()
+        .find(|o| o.path == "libcmt:printf.obj")
+        .unwrap();
+    assert_eq!(printf.archive.as_deref(), Some("libcmt"));
+    assert_eq!(printf.member.as_deref(), Some("printf.obj"));
+
+    let map = parse(Path::new("tests/fixtures/linker.map")).unwrap();
+    let kind_of = |index: usize| map.object_files[index].kind;
+    assert_eq!(kind_of(0), ObjectKind::LinkerSynthesized);
+    assert_eq!(kind_of(1), ObjectKind::TlvFile);
+    assert_eq!(kind_of(2), ObjectKind::Object);
+    assert_eq!(kind_of(66), ObjectKind::Dylib);
+}
+
+#[test]
+fn test_parse_str_borrows_names() {
+    let contents = std::fs::read_to_string("tests/fixtures/lld.map").unwrap();
+    let map = parse_str(&contents).unwrap();
+    assert!(map
+        .symbols
+        .iter()
+        .all(|symbol| matches!(symbol.name, Cow::Borrowed(_))));
+    assert!(map
+        .object_files
+        .iter()
+        .all(|object| matches!(object.path, Cow::Borrowed(_))));
+
+    let owned = map.clone().into_owned();
+    assert_eq!(owned, map);
+    assert_eq!(owned, parse(Path::new("tests/fixtures/lld.map")).unwrap());
+}
+
+#[test]
+fn test_loaded_contents_lend_names() {
+    let path = Path::new("tests/fixtures/mold.map");
+    let contents = load(path).unwrap();
+    let map = contents.parse().unwrap();
+    assert!(map
+        .symbols
+        .iter()
+        .all(|symbol| matches!(symbol.name, Cow::Borrowed(_))));
+    assert_eq!(map, parse(path).unwrap());
+    assert_eq!(
+        contents.parse_as(MapDialect::Mold).unwrap(),
+        parse_as(path, MapDialect::Mold).unwrap()
+    );
+
+    let file = std::fs::File::open("tests/fixtures/lld.map").unwrap();
+    let contents = load_reader(file).unwrap();
+    assert_eq!(
+        contents.parse().unwrap(),
+        parse_str(contents.as_str()).unwrap()
+    );
+}
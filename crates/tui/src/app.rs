@@ -1,24 +1,25 @@
 use std::io;
 use std::time::Instant;
 
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use crossterm::event::{self, Event, KeyEventKind};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
 
 use crate::event::handle_key;
-use crate::state::{ObjectsState, SymbolsState};
+use crate::state::{ObjectsState, SectionsState, SymbolsState};
 use crate::style::TICK_RATE;
 use crate::ui::render;
 
-use linkerland_metrics::build_metrics;
-use linkerland_parser::parse;
+use linkerland_metrics::{ContributionMetrics, Rules, build_metrics_with_rules};
+use linkerland_parser::MapFile;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FocusPane {
     Objects,
     Symbols,
+    Sections,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -33,49 +34,78 @@ pub struct AppState {
     pub binary_format: String,
     pub objects: ObjectsState,
     pub symbols: SymbolsState,
+    pub sections: SectionsState,
+    /// Bytes per object and section, by object id
+    pub contributions: Vec<ContributionMetrics>,
     pub focus: FocusPane,
     pub show_help: bool,
+    pub show_object_sections: bool,
     pub filter_mode: bool,
     pub last_tick: Instant,
     pub display_units: DisplayUnits,
 }
 
 impl AppState {
-    pub fn new(map_path: &str) -> Result<Self> {
-        let map =
-            parse(std::path::Path::new(map_path)).map_err(|e| anyhow!("parse error: {:?}", e))?;
-        let metrics = build_metrics(&map);
+    /// Sections the selected object contributes to, largest first.
+    pub fn current_object_sections(&self) -> Vec<&ContributionMetrics> {
+        let Some(id) = self.objects.current_object_id() else {
+            return Vec::new();
+        };
+        let start = self
+            .contributions
+            .partition_point(|cell| cell.object_id < id);
+        let mut cells: Vec<&ContributionMetrics> = self.contributions[start..]
+            .iter()
+            .take_while(|cell| cell.object_id == id)
+            .collect();
+        cells.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.section.cmp(&b.section)));
+        cells
+    }
+
+    pub fn new(map: MapFile<'_>, rules: &Rules) -> Self {
+        let mut metrics = build_metrics_with_rules(&map, rules);
+        // Names start out demangled; `d` switches back to the mangled ones.
+        metrics.demangle_symbols(true);
+        // Padding and unattributed bytes are listed like any other object.
+        let gaps = metrics.gaps;
+        metrics.objects.extend(gaps.objects);
+        metrics.symbols.extend(gaps.symbols);
+        metrics.contributions.extend(gaps.contributions);
 
         let mut objects = ObjectsState::new(metrics.objects);
         let mut symbols = SymbolsState::new(metrics.symbols);
+        let sections = SectionsState::new(metrics.sections);
 
         objects.filter();
         symbols.refresh_for_object(objects.current_object_id());
 
-        Ok(Self {
-            arch: map.arch,
+        Self {
+            arch: map.arch.into_owned(),
+            contributions: metrics.contributions,
             binary_format: map.binary_format.as_str().to_string(),
             display_units: DisplayUnits::Human,
             filter_mode: false,
             focus: FocusPane::Objects,
             last_tick: Instant::now(),
-            map_path: map.target_path,
+            map_path: map.target_path.into_owned(),
             objects,
+            sections,
             show_help: false,
+            show_object_sections: false,
             symbols,
-        })
+        }
     }
 }
 
-pub fn run(map_path: &str) -> Result<()> {
+pub fn run(map: MapFile<'_>, rules: &Rules) -> Result<()> {
+    let mut app = AppState::new(map, rules);
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = AppState::new(map_path)?;
-
     loop {
         terminal.draw(|f| render(f, &mut app))?;
         let elapsed = app.last_tick.elapsed();
@@ -12,6 +12,14 @@ pub fn handle_key(code: KeyCode, app: &mut AppState) -> Result<bool> {
         return Ok(false);
     }
 
+    if app.show_object_sections {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => app.show_object_sections = false,
+            _ => {}
+        }
+        return Ok(false);
+    }
+
     // Handle filter mode
     if app.filter_mode {
         match code {
@@ -27,6 +35,9 @@ pub fn handle_key(code: KeyCode, app: &mut AppState) -> Result<bool> {
                 FocusPane::Symbols => {
                     app.symbols.push_filter_char(c);
                 }
+                FocusPane::Sections => {
+                    app.sections.push_filter_char(c);
+                }
             },
             KeyCode::Backspace => match app.focus {
                 FocusPane::Objects => {
@@ -37,6 +48,9 @@ pub fn handle_key(code: KeyCode, app: &mut AppState) -> Result<bool> {
                 FocusPane::Symbols => {
                     app.symbols.pop_filter_char();
                 }
+                FocusPane::Sections => {
+                    app.sections.pop_filter_char();
+                }
             },
             _ => {}
         }
@@ -48,7 +62,8 @@ pub fn handle_key(code: KeyCode, app: &mut AppState) -> Result<bool> {
         KeyCode::Tab => {
             app.focus = match app.focus {
                 FocusPane::Objects => FocusPane::Symbols,
-                FocusPane::Symbols => FocusPane::Objects,
+                FocusPane::Symbols => FocusPane::Sections,
+                FocusPane::Sections => FocusPane::Objects,
             };
         }
         KeyCode::Char('/') => {
@@ -57,6 +72,9 @@ pub fn handle_key(code: KeyCode, app: &mut AppState) -> Result<bool> {
         KeyCode::Char('?') => {
             app.show_help = true;
         }
+        KeyCode::Enter if app.focus == FocusPane::Objects => {
+            app.show_object_sections = app.objects.current_object_id().is_some();
+        }
         KeyCode::Up => match app.focus {
             FocusPane::Objects => {
                 let prev_id = app.objects.current_object_id();
@@ -70,6 +88,9 @@ pub fn handle_key(code: KeyCode, app: &mut AppState) -> Result<bool> {
             FocusPane::Symbols => {
                 app.symbols.navigate_up();
             }
+            FocusPane::Sections => {
+                app.sections.navigate_up();
+            }
         },
         KeyCode::Down => match app.focus {
             FocusPane::Objects => {
@@ -84,6 +105,9 @@ pub fn handle_key(code: KeyCode, app: &mut AppState) -> Result<bool> {
             FocusPane::Symbols => {
                 app.symbols.navigate_down();
             }
+            FocusPane::Sections => {
+                app.sections.navigate_down();
+            }
         },
         KeyCode::Char('s') => match app.focus {
             FocusPane::Objects => {
@@ -92,6 +116,9 @@ pub fn handle_key(code: KeyCode, app: &mut AppState) -> Result<bool> {
             FocusPane::Symbols => {
                 app.symbols.cycle_sort_key();
             }
+            FocusPane::Sections => {
+                app.sections.cycle_sort_key();
+            }
         },
         KeyCode::Char('r') => match app.focus {
             FocusPane::Objects => {
@@ -100,7 +127,16 @@ pub fn handle_key(code: KeyCode, app: &mut AppState) -> Result<bool> {
             FocusPane::Symbols => {
                 app.symbols.toggle_sort_direction();
             }
+            FocusPane::Sections => {
+                app.sections.toggle_sort_direction();
+            }
         },
+        KeyCode::Char('k') => {
+            app.symbols.cycle_kind_filter();
+        }
+        KeyCode::Char('d') => {
+            app.symbols.toggle_demangle();
+        }
         KeyCode::Char('u') => {
             app.display_units = match app.display_units {
                 DisplayUnits::Human => DisplayUnits::Hex,
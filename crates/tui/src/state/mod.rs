@@ -1,5 +1,7 @@
 mod objects;
+mod sections;
 mod symbols;
 
 pub use objects::{ObjectSortKey, ObjectsState, SortDirection};
+pub use sections::{SectionSortKey, SectionsState};
 pub use symbols::{SymbolSortKey, SymbolsState};
@@ -4,6 +4,7 @@ use linkerland_metrics::ObjectMetrics;
 pub enum ObjectSortKey {
     Total,
     Text,
+    Rodata,
     Data,
     Bss,
     Path,
@@ -65,7 +66,7 @@ impl ObjectsState {
         self.filtered_indices.get(self.selected_position).copied()
     }
 
-    pub fn current_object_id(&self) -> Option<i32> {
+    pub fn current_object_id(&self) -> Option<u32> {
         self.current_index().map(|idx| self.objects[idx].id)
     }
 
@@ -101,6 +102,7 @@ impl ObjectsState {
             let ord = match key {
                 ObjectSortKey::Total => oa.total.cmp(&ob.total),
                 ObjectSortKey::Text => oa.text.cmp(&ob.text),
+                ObjectSortKey::Rodata => oa.rodata.cmp(&ob.rodata),
                 ObjectSortKey::Data => oa.data.cmp(&ob.data),
                 ObjectSortKey::Bss => oa.bss.cmp(&ob.bss),
                 ObjectSortKey::Path => oa.path.cmp(&ob.path),
@@ -125,7 +127,8 @@ impl ObjectsState {
     pub fn cycle_sort_key(&mut self) {
         self.sort_key = match self.sort_key {
             ObjectSortKey::Total => ObjectSortKey::Text,
-            ObjectSortKey::Text => ObjectSortKey::Data,
+            ObjectSortKey::Text => ObjectSortKey::Rodata,
+            ObjectSortKey::Rodata => ObjectSortKey::Data,
             ObjectSortKey::Data => ObjectSortKey::Bss,
             ObjectSortKey::Bss => ObjectSortKey::Path,
             ObjectSortKey::Path => ObjectSortKey::Total,
@@ -0,0 +1,170 @@
+use linkerland_metrics::SectionMetrics;
+
+use super::SortDirection;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionSortKey {
+    Address,
+    Size,
+    SymbolSize,
+    Symbols,
+    Name,
+}
+
+/// State for the Sections List
+pub struct SectionsState {
+    // Section data, in address order
+    sections: Vec<SectionMetrics>,
+
+    // Filter state
+    pub filter_text: String,
+    pub filtered_indices: Vec<usize>,
+
+    // Sort state
+    pub sort_key: SectionSortKey,
+    pub sort_direction: SortDirection,
+
+    // Scroll state
+    pub selected_position: usize,
+    pub scroll_offset: usize,
+
+    // Internal scroll tracking
+    visible_row_count: usize,
+}
+
+impl SectionsState {
+    pub fn new(data: Vec<SectionMetrics>) -> Self {
+        let filtered_indices: Vec<usize> = (0..data.len()).collect();
+        Self {
+            sections: data,
+            filtered_indices,
+            selected_position: 0,
+            scroll_offset: 0,
+            visible_row_count: 0,
+            filter_text: String::new(),
+            sort_key: SectionSortKey::Address,
+            sort_direction: SortDirection::Ascending,
+        }
+    }
+
+    pub fn sections(&self) -> &[SectionMetrics] {
+        &self.sections
+    }
+
+    pub fn set_view_rows(&mut self, rows: usize) {
+        self.visible_row_count = rows;
+    }
+
+    pub fn filter(&mut self) {
+        let needle = self.filter_text.to_ascii_lowercase();
+        self.filtered_indices = self
+            .sections
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| {
+                needle.is_empty()
+                    || s.segment.to_ascii_lowercase().contains(&needle)
+                    || s.section.to_ascii_lowercase().contains(&needle)
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        self.sort();
+
+        if self.selected_position >= self.filtered_indices.len() {
+            self.selected_position = self.filtered_indices.len().saturating_sub(1);
+        }
+        self.ensure_visible();
+    }
+
+    pub fn sort(&mut self) {
+        let key = self.sort_key;
+        let direction = self.sort_direction;
+
+        self.filtered_indices.sort_by(|&a, &b| {
+            let sa = &self.sections[a];
+            let sb = &self.sections[b];
+            let ord = match key {
+                // Sections are stored in address order already.
+                SectionSortKey::Address => a.cmp(&b),
+                SectionSortKey::Size => sa.size.cmp(&sb.size),
+                SectionSortKey::SymbolSize => sa.symbol_size.cmp(&sb.symbol_size),
+                SectionSortKey::Symbols => sa.symbols.cmp(&sb.symbols),
+                SectionSortKey::Name => (&sa.segment, &sa.section).cmp(&(&sb.segment, &sb.section)),
+            };
+            match direction {
+                SortDirection::Ascending => ord,
+                SortDirection::Descending => ord.reverse(),
+            }
+        });
+    }
+
+    pub fn push_filter_char(&mut self, c: char) {
+        self.filter_text.push(c);
+        self.filter();
+    }
+
+    pub fn pop_filter_char(&mut self) {
+        self.filter_text.pop();
+        self.filter();
+    }
+
+    pub fn cycle_sort_key(&mut self) {
+        self.sort_key = match self.sort_key {
+            SectionSortKey::Address => SectionSortKey::Size,
+            SectionSortKey::Size => SectionSortKey::SymbolSize,
+            SectionSortKey::SymbolSize => SectionSortKey::Symbols,
+            SectionSortKey::Symbols => SectionSortKey::Name,
+            SectionSortKey::Name => SectionSortKey::Address,
+        };
+        self.sort();
+    }
+
+    pub fn toggle_sort_direction(&mut self) {
+        self.sort_direction = match self.sort_direction {
+            SortDirection::Ascending => SortDirection::Descending,
+            SortDirection::Descending => SortDirection::Ascending,
+        };
+        self.sort();
+    }
+
+    pub fn navigate_up(&mut self) -> bool {
+        if self.selected_position > 0 {
+            self.selected_position -= 1;
+            self.ensure_visible();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn navigate_down(&mut self) -> bool {
+        if self.selected_position + 1 < self.filtered_indices.len() {
+            self.selected_position += 1;
+            self.ensure_visible();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn ensure_visible(&mut self) {
+        if self.visible_row_count == 0 {
+            return;
+        }
+
+        if self.selected_position < self.scroll_offset {
+            self.scroll_offset = self.selected_position;
+        } else if self.selected_position >= self.scroll_offset + self.visible_row_count {
+            self.scroll_offset = self.selected_position + 1 - self.visible_row_count;
+        }
+
+        let max_offset = self
+            .filtered_indices
+            .len()
+            .saturating_sub(self.visible_row_count);
+        if self.scroll_offset > max_offset {
+            self.scroll_offset = max_offset;
+        }
+    }
+}
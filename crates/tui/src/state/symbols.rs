@@ -1,4 +1,5 @@
 use linkerland_metrics::SymbolMetrics;
+use linkerland_parser::SymbolKind;
 
 use super::SortDirection;
 
@@ -13,12 +14,16 @@ pub enum SymbolSortKey {
 pub struct SymbolsState {
     // Symbol data
     symbols: Vec<SymbolMetrics>,
-    current_object_id: Option<i32>,
+    current_object_id: Option<u32>,
 
     // Filter state
     pub filter_text: String,
+    pub kind_filter: Option<SymbolKind>,
     pub filtered_indices: Vec<usize>,
 
+    // Display state
+    pub demangle: bool,
+
     // Sort state
     pub sort_key: SymbolSortKey,
     pub sort_direction: SortDirection,
@@ -40,6 +45,8 @@ impl SymbolsState {
             offset: 0,
             view_rows: 0,
             filter_text: String::new(),
+            kind_filter: None,
+            demangle: true,
             sort_key: SymbolSortKey::Size,
             sort_direction: SortDirection::Descending,
             current_object_id: None,
@@ -50,11 +57,16 @@ impl SymbolsState {
         &self.symbols
     }
 
+    /// Name of `symbol` as shown: demangled if enabled and it is a mangled Rust or C++ symbol.
+    pub fn display_name<'a>(&self, symbol: &'a SymbolMetrics) -> &'a str {
+        display_name(symbol, self.demangle)
+    }
+
     pub fn set_view_rows(&mut self, rows: usize) {
         self.view_rows = rows;
     }
 
-    pub fn refresh_for_object(&mut self, object_id: Option<i32>) {
+    pub fn refresh_for_object(&mut self, object_id: Option<u32>) {
         self.current_object_id = object_id;
 
         let Some(obj_id) = object_id else {
@@ -68,10 +80,11 @@ impl SymbolsState {
             .iter()
             .enumerate()
             .filter(|(_, sym)| sym.file_index == obj_id)
+            .filter(|(_, sym)| self.kind_filter.is_none_or(|kind| sym.kind == kind))
             .filter(|(_, sym)| {
                 self.filter_text.is_empty()
-                    || sym
-                        .name
+                    || self
+                        .display_name(sym)
                         .to_ascii_lowercase()
                         .contains(&self.filter_text.to_ascii_lowercase())
             })
@@ -92,6 +105,7 @@ impl SymbolsState {
     pub fn sort(&mut self) {
         let key = self.sort_key;
         let direction = self.sort_direction;
+        let demangle = self.demangle;
 
         self.filtered_indices.sort_by(|&a, &b| {
             let sa = &self.symbols[a];
@@ -99,7 +113,7 @@ impl SymbolsState {
             let ord = match key {
                 SymbolSortKey::Size => sa.size.cmp(&sb.size),
                 SymbolSortKey::Address => sa.address.cmp(&sb.address),
-                SymbolSortKey::Name => sa.name.cmp(&sb.name),
+                SymbolSortKey::Name => display_name(sa, demangle).cmp(display_name(sb, demangle)),
             };
             match direction {
                 SortDirection::Ascending => ord,
@@ -118,6 +132,23 @@ impl SymbolsState {
         self.filter();
     }
 
+    /// Step the kind filter through every [`SymbolKind`], then back to showing all kinds.
+    pub fn cycle_kind_filter(&mut self) {
+        let next = match self.kind_filter {
+            None => 0,
+            Some(kind) => SymbolKind::ALL.iter().position(|&k| k == kind).unwrap_or(0) + 1,
+        };
+        self.kind_filter = SymbolKind::ALL.get(next).copied();
+        self.filter();
+        self.reset_selection();
+    }
+
+    /// Switch names between their demangled and mangled forms.
+    pub fn toggle_demangle(&mut self) {
+        self.demangle = !self.demangle;
+        self.filter();
+    }
+
     pub fn cycle_sort_key(&mut self) {
         self.sort_key = match self.sort_key {
             SymbolSortKey::Size => SymbolSortKey::Address,
@@ -171,3 +202,10 @@ impl SymbolsState {
         self.ensure_visible();
     }
 }
+
+fn display_name(symbol: &SymbolMetrics, demangle: bool) -> &str {
+    match &symbol.demangled_name {
+        Some(demangled) if demangle => demangled,
+        _ => &symbol.name,
+    }
+}
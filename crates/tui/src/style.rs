@@ -20,3 +20,7 @@ pub fn objects_block_title() -> &'static str {
 pub fn symbols_block_title() -> &'static str {
     " SYMBOLS "
 }
+
+pub fn sections_block_title() -> &'static str {
+    " SECTIONS "
+}
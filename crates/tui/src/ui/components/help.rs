@@ -25,7 +25,10 @@ pub fn render_help(frame: &mut Frame, _app: &AppState) {
         )]),
         Line::from(vec![Span::raw("  ↑↓        Navigate up/down")]),
         Line::from(vec![Span::raw(
-            "  Tab       Switch between Objects and Symbols panes",
+            "  Tab       Cycle Objects, Symbols and Sections panes",
+        )]),
+        Line::from(vec![Span::raw(
+            "  Enter     Show the selected object's bytes per section",
         )]),
         Line::from(""),
         Line::from(vec![Span::styled(
@@ -39,6 +42,7 @@ pub fn render_help(frame: &mut Frame, _app: &AppState) {
         Line::from(vec![Span::raw("  Backspace Delete last character")]),
         Line::from(vec![Span::raw("  Esc       Exit filter mode")]),
         Line::from(vec![Span::raw("  Enter     Exit filter mode")]),
+        Line::from(vec![Span::raw("  k         Cycle symbol kind filter")]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Sorting & Display:",
@@ -49,6 +53,9 @@ pub fn render_help(frame: &mut Frame, _app: &AppState) {
         Line::from(vec![Span::raw("  s         Cycle sort key")]),
         Line::from(vec![Span::raw("  r         Reverse sort order")]),
         Line::from(vec![Span::raw("  u         Toggle units (human ↔ hex)")]),
+        Line::from(vec![Span::raw(
+            "  d         Toggle symbol names (demangled ↔ mangled)",
+        )]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "General:",
@@ -1,13 +1,17 @@
 mod header;
 mod help;
+mod object_sections;
 mod objects;
+mod sections;
 mod shared;
 mod status;
 mod symbols;
 
 pub use header::render_header;
 pub use help::render_help;
+pub use object_sections::render_object_sections;
 pub use objects::render_objects;
+pub use sections::render_sections;
 pub use shared::truncate_path;
 pub use status::render_status;
 pub use symbols::render_symbols;
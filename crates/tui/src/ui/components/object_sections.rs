@@ -0,0 +1,67 @@
+use ratatui::Frame;
+use ratatui::layout::Constraint;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Block, Borders, Cell, Clear, Row, Table};
+
+use super::shared::centered_rect;
+use crate::app::AppState;
+use crate::style::header_style;
+use crate::units::format_size;
+
+/// Drill-down of the selected object: the bytes it contributes to each output section.
+pub fn render_object_sections(frame: &mut Frame, app: &AppState) {
+    let Some(idx) = app.objects.current_index() else {
+        return;
+    };
+    let object = &app.objects.objects()[idx];
+    let area = centered_rect(70, 60, frame.area());
+
+    let header = Row::new(vec![
+        Cell::from("Size"),
+        Cell::from("Symbols"),
+        Cell::from("Of section"),
+        Cell::from("Section"),
+    ])
+    .style(header_style());
+    let sections = app.sections.sections();
+    let rows = app.current_object_sections().into_iter().map(|cell| {
+        let section = &sections[cell.section];
+        let share = if section.size == 0 {
+            0.0
+        } else {
+            cell.size as f64 * 100.0 / section.size as f64
+        };
+        let name = if section.segment == section.section {
+            section.section.clone()
+        } else {
+            format!("{},{}", section.segment, section.section)
+        };
+        Row::new(vec![
+            Cell::from(format_size(cell.size, app.display_units)),
+            Cell::from(cell.symbols.to_string()),
+            Cell::from(format!("{share:.1}%")),
+            Cell::from(name),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        &[
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(11),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", object.name))
+            .title_bottom(" Esc or Enter to close ")
+            .border_style(Style::default().fg(Color::LightGreen))
+            .padding(ratatui::widgets::Padding::horizontal(1)),
+    )
+    .column_spacing(1);
+    frame.render_widget(Clear, area);
+    frame.render_widget(table, area);
+}
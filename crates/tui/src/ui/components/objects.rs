@@ -36,6 +36,8 @@ pub fn render_objects(frame: &mut Frame, area: Rect, app: &mut AppState) {
     let header = Row::new(vec![
         Cell::from(make_label("TEXT", ObjectSortKey::Text))
             .style(make_style(Color::LightMagenta, ObjectSortKey::Text)),
+        Cell::from(make_label("RODATA", ObjectSortKey::Rodata))
+            .style(make_style(Color::LightRed, ObjectSortKey::Rodata)),
         Cell::from(make_label("DATA", ObjectSortKey::Data))
             .style(make_style(Color::LightCyan, ObjectSortKey::Data)),
         Cell::from(make_label("BSS", ObjectSortKey::Bss))
@@ -72,11 +74,12 @@ pub fn render_objects(frame: &mut Frame, area: Rect, app: &mut AppState) {
             };
             Row::new(vec![
                 Cell::from(format_size(o.text, app.display_units)),
+                Cell::from(format_size(o.rodata, app.display_units)),
                 Cell::from(format_size(o.data, app.display_units)),
                 Cell::from(format_size(o.bss, app.display_units)),
                 Cell::from(format_size(o.total, app.display_units)),
                 Cell::from(truncate_path(
-                    &o.path,
+                    &o.name,
                     area.width.saturating_sub(18) as usize,
                 )),
             ])
@@ -89,6 +92,7 @@ pub fn render_objects(frame: &mut Frame, area: Rect, app: &mut AppState) {
             Constraint::Length(12),
             Constraint::Length(12),
             Constraint::Length(12),
+            Constraint::Length(12),
             Constraint::Min(10),
         ],
     )
@@ -0,0 +1,120 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, Cell, Row, Table};
+
+use crate::app::{AppState, FocusPane};
+use crate::state::{SectionSortKey, SortDirection};
+use crate::style::{header_style, sections_block_title, selection_style};
+use crate::units::format_size;
+
+pub fn render_sections(frame: &mut Frame, area: Rect, app: &mut AppState) {
+    let arrow = match app.sections.sort_direction {
+        SortDirection::Ascending => " ↑",
+        SortDirection::Descending => " ↓",
+    };
+    let sort_key = app.sections.sort_key;
+
+    let make_label = |base: &str, key: SectionSortKey| {
+        if sort_key == key {
+            format!("{}{}", base, arrow)
+        } else {
+            base.to_string()
+        }
+    };
+
+    let make_style = |key: SectionSortKey| {
+        if sort_key == key {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        }
+    };
+
+    let header = Row::new(vec![
+        Cell::from(make_label("Addr", SectionSortKey::Address))
+            .style(make_style(SectionSortKey::Address)),
+        Cell::from(make_label("Size", SectionSortKey::Size))
+            .style(make_style(SectionSortKey::Size)),
+        Cell::from(make_label("Symbols", SectionSortKey::SymbolSize))
+            .style(make_style(SectionSortKey::SymbolSize)),
+        Cell::from(make_label("Count", SectionSortKey::Symbols))
+            .style(make_style(SectionSortKey::Symbols)),
+        Cell::from("Padding"),
+        Cell::from("Unknown"),
+        Cell::from("Overlap"),
+        Cell::from("Bucket"),
+        Cell::from(make_label("Section", SectionSortKey::Name))
+            .style(make_style(SectionSortKey::Name)),
+        Cell::from("Largest symbol"),
+    ])
+    .style(header_style());
+    let body_rows = area.height.saturating_sub(3) as usize; // header + borders
+    app.sections.set_view_rows(body_rows);
+    let start = app.sections.scroll_offset;
+    let end = (start + body_rows).min(app.sections.filtered_indices.len());
+    let rows = app.sections.filtered_indices[start..end]
+        .iter()
+        .enumerate()
+        .map(|(i, &sec_idx)| {
+            let actual_index = start + i;
+            let s = &app.sections.sections()[sec_idx];
+            let style = if actual_index == app.sections.selected_position
+                && app.focus == FocusPane::Sections
+            {
+                selection_style()
+            } else {
+                Style::default()
+            };
+            let name = if s.segment == s.section {
+                s.section.clone()
+            } else {
+                format!("{},{}", s.segment, s.section)
+            };
+            let largest = match &s.largest_symbol {
+                Some(symbol) => format!(
+                    "{} ({})",
+                    symbol,
+                    format_size(s.largest_symbol_size, app.display_units)
+                ),
+                None => String::new(),
+            };
+            Row::new(vec![
+                Cell::from(format!("0x{:08X}", s.start)),
+                Cell::from(format_size(s.size, app.display_units)),
+                Cell::from(format_size(s.symbol_size, app.display_units)),
+                Cell::from(s.symbols.to_string()),
+                Cell::from(format_size(s.padding, app.display_units)),
+                Cell::from(format_size(s.unknown, app.display_units)),
+                Cell::from(format_size(s.overlap, app.display_units)),
+                Cell::from(s.bucket.as_str()),
+                Cell::from(name),
+                Cell::from(largest),
+            ])
+            .style(style)
+        });
+    let table = Table::new(
+        rows,
+        &[
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(28),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(sections_block_title())
+            .padding(ratatui::widgets::Padding::horizontal(1)),
+    )
+    .column_spacing(1);
+    frame.render_widget(table, area);
+}
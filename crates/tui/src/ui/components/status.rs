@@ -14,7 +14,7 @@ pub fn render_status(frame: &mut Frame, area: Rect, app: &AppState) {
 
     let mut spans = vec![
         Span::styled(
-            " ↑↓ navigate  |  / filter  | s sort | r reverse | u units | <Tab> pane | q quit | ? help  |  ",
+            " ↑↓ navigate  |  / filter  | k kind | s sort | r reverse | u units | d demangle | <Tab> pane | <Enter> sections | q quit | ? help  |  ",
             Style::default().fg(Color::Gray),
         ),
         Span::styled("units: ", Style::default().fg(Color::Gray)),
@@ -25,10 +25,10 @@ pub fn render_status(frame: &mut Frame, area: Rect, app: &AppState) {
     ];
 
     if app.filter_mode {
-        let current_filter = if app.focus == FocusPane::Objects {
-            &app.objects.filter_text
-        } else {
-            &app.symbols.filter_text
+        let current_filter = match app.focus {
+            FocusPane::Objects => &app.objects.filter_text,
+            FocusPane::Symbols => &app.symbols.filter_text,
+            FocusPane::Sections => &app.sections.filter_text,
         };
 
         spans.push(Span::styled(
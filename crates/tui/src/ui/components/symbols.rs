@@ -38,6 +38,10 @@ pub fn render_symbols(frame: &mut Frame, area: Rect, app: &mut AppState) {
             .style(make_style(SymbolSortKey::Address)),
         Cell::from(make_label("Size", SymbolSortKey::Size)).style(make_style(SymbolSortKey::Size)),
         Cell::from("Bucket"),
+        Cell::from(match app.symbols.kind_filter {
+            Some(kind) => format!("Kind: {}", kind.as_str()),
+            None => "Kind".to_string(),
+        }),
         Cell::from(make_label("Name", SymbolSortKey::Name)).style(make_style(SymbolSortKey::Name)),
     ])
     .style(header_style());
@@ -57,17 +61,22 @@ pub fn render_symbols(frame: &mut Frame, area: Rect, app: &mut AppState) {
                 } else {
                     Style::default()
                 };
-            let bucket_span = match s.bucket {
+            let bucket_span = match &s.bucket {
                 Bucket::Text => Span::styled("TEXT", Style::default().fg(Color::Magenta)),
+                Bucket::Rodata => Span::styled("RODATA", Style::default().fg(Color::Red)),
                 Bucket::Data => Span::styled("DATA", Style::default().fg(Color::Yellow)),
                 Bucket::Bss => Span::styled("BSS", Style::default().fg(Color::Green)),
                 Bucket::Other => Span::raw("OTHER"),
+                Bucket::Custom(name) => {
+                    Span::styled(name.to_string(), Style::default().fg(Color::Cyan))
+                }
             };
             Row::new(vec![
                 Cell::from(format!("0x{:08X}", s.address)),
                 Cell::from(format_size(s.size, app.display_units)),
                 Cell::from(Line::from(bucket_span)),
-                Cell::from(s.name.clone()),
+                Cell::from(s.kind.as_str()),
+                Cell::from(app.symbols.display_name(s).to_string()),
             ])
             .style(style)
         });
@@ -77,6 +86,7 @@ pub fn render_symbols(frame: &mut Frame, area: Rect, app: &mut AppState) {
             Constraint::Length(12),
             Constraint::Length(12),
             Constraint::Length(8),
+            Constraint::Length(18),
             Constraint::Min(10),
         ],
     )
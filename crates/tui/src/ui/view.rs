@@ -3,7 +3,8 @@ use ratatui::layout::{Constraint, Direction, Layout};
 use ratatui::widgets::{Block, Borders};
 
 use super::components::{
-    render_header, render_help, render_objects, render_status, render_symbols,
+    render_header, render_help, render_object_sections, render_objects, render_sections,
+    render_status, render_symbols,
 };
 use crate::app::AppState;
 
@@ -24,15 +25,25 @@ pub fn render(frame: &mut Frame, app: &mut AppState) {
 
     render_header(frame, layout[0], app);
 
+    let body_rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+        .split(layout[1]);
+
     let body_cols = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
-        .split(layout[1]);
+        .split(body_rows[0]);
     render_objects(frame, body_cols[0], app);
     render_symbols(frame, body_cols[1], app);
+    render_sections(frame, body_rows[1], app);
 
     render_status(frame, layout[2], app);
 
+    if app.show_object_sections {
+        render_object_sections(frame, app);
+    }
+
     if app.show_help {
         render_help(frame, app);
     }